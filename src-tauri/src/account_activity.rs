@@ -0,0 +1,484 @@
+// Antigravity 账户使用时长统计模块
+// 按账户记录使用会话，并汇总为按天 / 按账户的使用时长
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::account_registry::{self, AccountEntry};
+use crate::config_manager::ConfigManager;
+
+/// 进程监控轮询间隔（托盘状态也使用该监控的结果）
+const MONITOR_INTERVAL_SECS: u64 = 5;
+
+/// 进程持续运行时，会话最后活跃时间的更新间隔
+const SESSION_UPDATE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// 会话记录保留天数
+const RETENTION_DAYS: i64 = 365;
+
+/// 会话文件读写锁
+static ACTIVITY_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// 会话结束原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEndReason {
+    /// 切换到了其他账户
+    Switched,
+    /// 执行了登出（清除认证数据）
+    Logout,
+    /// Antigravity 进程退出
+    ProcessExited,
+}
+
+/// 单个账户使用会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivitySession {
+    pub account_id: String,
+    pub email: String,
+    /// 会话开始时间：切换时先记录切换时间，首次观察到进程运行时更新为该时间
+    pub started_at: String,
+    #[serde(default)]
+    pub ended_at: Option<String>,
+    #[serde(default)]
+    pub end_reason: Option<SessionEndReason>,
+    /// 最近一次观察到 Antigravity 进程运行的时间
+    ///
+    /// 进程退出时以该时间作为会话结束时间，避免把 Agent 未运行的时段计入
+    #[serde(default)]
+    pub last_seen_running: Option<String>,
+}
+
+/// 会话存储文件结构
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ActivityLog {
+    #[serde(default)]
+    active: Option<ActivitySession>,
+    #[serde(default)]
+    sessions: Vec<ActivitySession>,
+}
+
+/// 某一天某个账户的使用时长
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyUsage {
+    pub date: String,
+    pub account_id: String,
+    pub email: String,
+    pub active_seconds: i64,
+    pub sessions: u32,
+}
+
+/// 某个账户在统计区间内的使用时长
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountUsage {
+    pub account_id: String,
+    pub email: String,
+    pub active_seconds: i64,
+    pub sessions: u32,
+}
+
+/// 使用时长汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageSummary {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub days: Vec<DailyUsage>,
+    pub accounts: Vec<AccountUsage>,
+    pub active_session: Option<ActivitySession>,
+}
+
+/// 读取会话文件，文件不存在时返回空记录
+fn read_activity_log(path: &Path) -> Result<ActivityLog, String> {
+    if !path.exists() {
+        return Ok(ActivityLog::default());
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("读取使用记录失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析使用记录失败: {}", e))
+}
+
+/// 在持有锁的情况下读取、修改并写回会话文件
+///
+/// 现有文件读取或解析失败时直接返回错误，不写回，避免覆盖已有的使用记录
+fn with_activity_log<T>(f: impl FnOnce(&mut ActivityLog) -> T) -> Result<T, String> {
+    let _guard = ACTIVITY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = ConfigManager::new()?.activity_file();
+
+    let mut log = read_activity_log(&path)?;
+    let result = f(&mut log);
+
+    // 先写临时文件再替换，避免写入中断导致使用记录丢失
+    let content =
+        serde_json::to_string_pretty(&log).map_err(|e| format!("序列化使用记录失败: {}", e))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("写入使用记录失败: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("替换使用记录文件失败: {}", e))?;
+    Ok(result)
+}
+
+/// 只读方式加载会话文件
+fn load_activity_log() -> Result<ActivityLog, String> {
+    let _guard = ACTIVITY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = ConfigManager::new()?.activity_file();
+    read_activity_log(&path)
+}
+
+impl ActivityLog {
+    /// 结束当前会话并归档
+    ///
+    /// 从未观察到 Antigravity 运行的会话（例如原地切换后一直没有打开）不计入使用时长，直接丢弃
+    fn close_active(&mut self, reason: SessionEndReason, ended_at: String) {
+        if let Some(mut session) = self.active.take() {
+            if session.last_seen_running.is_none() {
+                println!("⏹️ 丢弃未使用的账户会话: {} ({:?})", session.email, reason);
+            } else {
+                println!("⏹️ 结束账户会话: {} ({:?})", session.email, reason);
+                session.ended_at = Some(ended_at);
+                session.end_reason = Some(reason);
+                self.sessions.push(session);
+            }
+        }
+
+        // 清理过期记录
+        let cutoff = Local::now() - chrono::Duration::days(RETENTION_DAYS);
+        self.sessions.retain(|s| {
+            s.ended_at
+                .as_deref()
+                .and_then(parse_time)
+                .is_none_or(|ended| ended > cutoff)
+        });
+    }
+}
+
+/// 开始一个新的账户会话（切换或启动成功时调用）
+///
+/// 如果已有其他账户的会话，会以“切换”为原因结束它；同一账户的会话会被沿用
+pub fn start_session(entry: &AccountEntry) {
+    let result = with_activity_log(|log| {
+        if log
+            .active
+            .as_ref()
+            .is_some_and(|active| active.account_id == entry.id)
+        {
+            return;
+        }
+
+        let now = Local::now().to_rfc3339();
+        log.close_active(SessionEndReason::Switched, now.clone());
+
+        println!("▶️ 开始账户会话: {}", entry.email);
+        log.active = Some(ActivitySession {
+            account_id: entry.id.clone(),
+            email: entry.email.clone(),
+            started_at: now,
+            ended_at: None,
+            end_reason: None,
+            last_seen_running: None,
+        });
+    });
+
+    if let Err(e) = result {
+        log::warn!("⚠️ 记录账户会话失败: {}", e);
+    }
+}
+
+/// 为注册表中的当前账户开始会话（例如 Antigravity 启动成功后）
+pub fn start_session_for_current_account() {
+    match account_registry::load_registry() {
+        Ok(registry) => {
+            let current = registry
+                .current_account_id
+                .as_deref()
+                .and_then(|id| registry.resolve(id));
+            if let Some(entry) = current {
+                start_session(entry);
+            }
+        }
+        Err(e) => log::warn!("⚠️ 读取当前账户失败: {}", e),
+    }
+}
+
+/// 结束当前账户会话
+pub fn end_session(reason: SessionEndReason) {
+    let result = with_activity_log(|log| log.close_active(reason, Local::now().to_rfc3339()));
+    if let Err(e) = result {
+        log::warn!("⚠️ 结束账户会话失败: {}", e);
+    }
+}
+
+/// 启动 Antigravity 进程监控
+///
+/// 定期检查进程状态并记录（运行状态变化时刷新托盘）：首次观察到进程运行时以该时间作为会话开始时间，
+/// 运行期间刷新会话的最后活跃时间，进程退出后以最后活跃时间结束会话。
+/// Antigravity 在 Agent 之外启动（例如从程序坞或终端打开）时，为当前账户开始新的会话
+pub fn start_process_monitor() {
+    tauri::async_runtime::spawn(async {
+        println!("👀 账户使用时长监控已启动");
+        let mut last_session_update: Option<std::time::Instant> = None;
+        loop {
+            let running = crate::platform_utils::is_antigravity_process_running();
+            let changed = crate::antigravity_process::record_running_state(running);
+            if changed {
+                crate::system_tray::SystemTrayManager::refresh_status();
+                // 同一账户已有会话时会沿用，不会重复开始
+                if running {
+                    start_session_for_current_account();
+                }
+            }
+
+            // 运行状态未变化时按较长的间隔更新会话，减少写入
+            let due = changed
+                || last_session_update.is_none_or(|t| t.elapsed() >= SESSION_UPDATE_INTERVAL);
+            if due {
+                last_session_update = Some(std::time::Instant::now());
+                update_active_session(running);
+            }
+
+            tokio::time::sleep(tokio::time::Duration::from_secs(MONITOR_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+/// 根据进程运行状态更新当前会话
+fn update_active_session(running: bool) {
+    let has_active_session = load_activity_log()
+        .map(|log| log.active.is_some())
+        .unwrap_or(false);
+    if !has_active_session {
+        return;
+    }
+
+    let result = with_activity_log(|log| {
+        let Some(active) = log.active.as_mut() else {
+            return;
+        };
+
+        if running {
+            let now = Local::now().to_rfc3339();
+            // 切换后 Antigravity 尚未运行的时段不计入
+            if active.last_seen_running.is_none() {
+                active.started_at = now.clone();
+            }
+            active.last_seen_running = Some(now);
+        } else if let Some(last_seen) = active.last_seen_running.clone() {
+            // 只有在本次会话中观察到过进程运行，才认为进程已退出
+            log.close_active(SessionEndReason::ProcessExited, last_seen);
+        }
+    });
+
+    if let Err(e) = result {
+        log::warn!("⚠️ 更新账户会话失败: {}", e);
+    }
+}
+
+/// 解析 RFC3339 时间为本地时间
+fn parse_time(value: &str) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Local))
+}
+
+/// 将时间段按本地自然日拆分，返回每天的秒数
+fn split_by_day(start: DateTime<Local>, end: DateTime<Local>) -> Vec<(NaiveDate, i64)> {
+    let mut segments = Vec::new();
+    let mut cursor = start;
+
+    while cursor < end {
+        let date = cursor.date_naive();
+        let next_midnight = date
+            .succ_opt()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .and_then(|t| Local.from_local_datetime(&t).earliest())
+            .unwrap_or(end);
+        let segment_end = next_midnight.min(end);
+        if segment_end <= cursor {
+            break;
+        }
+
+        segments.push((date, (segment_end - cursor).num_seconds()));
+        cursor = segment_end;
+    }
+
+    segments
+}
+
+/// 计算会话参与统计的时间段
+///
+/// 已结束的会话使用结束时间；进行中的会话在进程运行时统计到当前时间，
+/// 进程未运行时只统计到最后一次观察到进程运行的时间，从未观察到运行时不计入
+fn session_span(
+    session: &ActivitySession,
+    now: DateTime<Local>,
+    running: bool,
+) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    let start = parse_time(&session.started_at)?;
+    let end = match session.ended_at.as_deref() {
+        Some(ended_at) => parse_time(ended_at)?,
+        None => {
+            let last_seen = parse_time(session.last_seen_running.as_deref()?)?;
+            if running {
+                now
+            } else {
+                last_seen
+            }
+        }
+    };
+    Some((start, end))
+}
+
+/// 汇总指定日期区间（包含首尾，格式 YYYY-MM-DD）内的使用时长
+pub fn usage_summary(
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<UsageSummary, String> {
+    let log = load_activity_log()?;
+    let now = Local::now();
+    let running = crate::antigravity_process::last_known_running();
+
+    // (日期, 账户 ID) -> (邮箱, 秒数, 会话数)
+    let mut daily: BTreeMap<(NaiveDate, String), (String, i64, u32)> = BTreeMap::new();
+
+    for session in log.sessions.iter().chain(log.active.iter()) {
+        let Some((start, end)) = session_span(session, now, running) else {
+            continue;
+        };
+
+        for (index, (date, seconds)) in split_by_day(start, end).into_iter().enumerate() {
+            if from.is_some_and(|f| date < f) || to.is_some_and(|t| date > t) {
+                continue;
+            }
+
+            let item = daily
+                .entry((date, session.account_id.clone()))
+                .or_insert_with(|| (session.email.clone(), 0, 0));
+            item.1 += seconds;
+            // 跨天的会话只在开始的那天计入会话数
+            if index == 0 {
+                item.2 += 1;
+            }
+        }
+    }
+
+    let mut accounts: BTreeMap<String, AccountUsage> = BTreeMap::new();
+    let days = daily
+        .into_iter()
+        .map(|((date, account_id), (email, active_seconds, sessions))| {
+            let total = accounts
+                .entry(account_id.clone())
+                .or_insert_with(|| AccountUsage {
+                    account_id: account_id.clone(),
+                    email: email.clone(),
+                    active_seconds: 0,
+                    sessions: 0,
+                });
+            total.active_seconds += active_seconds;
+            total.sessions += sessions;
+
+            DailyUsage {
+                date: date.format("%Y-%m-%d").to_string(),
+                account_id,
+                email,
+                active_seconds,
+                sessions,
+            }
+        })
+        .collect();
+
+    Ok(UsageSummary {
+        from: from.map(|d| d.format("%Y-%m-%d").to_string()),
+        to: to.map(|d| d.format("%Y-%m-%d").to_string()),
+        days,
+        accounts: accounts.into_values().collect(),
+        active_session: log.active,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::local;
+
+    fn session(
+        started_at: &str,
+        ended_at: Option<&str>,
+        last_seen: Option<&str>,
+    ) -> ActivitySession {
+        ActivitySession {
+            account_id: "id".to_string(),
+            email: "a@example.com".to_string(),
+            started_at: local(started_at).to_rfc3339(),
+            ended_at: ended_at.map(|t| local(t).to_rfc3339()),
+            end_reason: None,
+            last_seen_running: last_seen.map(|t| local(t).to_rfc3339()),
+        }
+    }
+
+    #[test]
+    fn split_by_day_within_one_day() {
+        let segments = split_by_day(local("2025-06-10 09:00:00"), local("2025-06-10 10:30:00"));
+        assert_eq!(
+            segments,
+            vec![(NaiveDate::from_ymd_opt(2025, 6, 10).unwrap(), 90 * 60)]
+        );
+    }
+
+    #[test]
+    fn split_by_day_across_midnight() {
+        let segments = split_by_day(local("2025-06-10 23:00:00"), local("2025-06-11 01:00:00"));
+        assert_eq!(
+            segments,
+            vec![
+                (NaiveDate::from_ymd_opt(2025, 6, 10).unwrap(), 3600),
+                (NaiveDate::from_ymd_opt(2025, 6, 11).unwrap(), 3600),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_by_day_empty_range() {
+        let time = local("2025-06-10 09:00:00");
+        assert!(split_by_day(time, time).is_empty());
+    }
+
+    #[test]
+    fn open_session_never_seen_running_is_not_counted() {
+        let now = local("2025-06-10 12:00:00");
+        let s = session("2025-06-10 09:00:00", None, None);
+        assert_eq!(session_span(&s, now, false), None);
+        assert_eq!(session_span(&s, now, true), None);
+    }
+
+    #[test]
+    fn open_session_is_capped_at_last_seen_when_not_running() {
+        let now = local("2025-06-10 12:00:00");
+        let s = session("2025-06-10 09:00:00", None, Some("2025-06-10 10:00:00"));
+
+        assert_eq!(
+            session_span(&s, now, false),
+            Some((local("2025-06-10 09:00:00"), local("2025-06-10 10:00:00")))
+        );
+        assert_eq!(
+            session_span(&s, now, true),
+            Some((local("2025-06-10 09:00:00"), now))
+        );
+    }
+
+    #[test]
+    fn closed_session_uses_end_time() {
+        let now = local("2025-06-10 12:00:00");
+        let s = session(
+            "2025-06-10 09:00:00",
+            Some("2025-06-10 09:30:00"),
+            Some("2025-06-10 09:30:00"),
+        );
+        assert_eq!(
+            session_span(&s, now, false),
+            Some((local("2025-06-10 09:00:00"), local("2025-06-10 09:30:00")))
+        );
+    }
+}
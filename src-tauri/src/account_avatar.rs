@@ -0,0 +1,261 @@
+// Antigravity 账户头像模块
+// 负责从账户备份中解码 antigravity.profileUrl 头像，并生成缩略图缓存
+
+use base64::Engine;
+use image::{imageops::FilterType, ImageFormat};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::account_registry::{self, AccountEntry};
+use crate::config_manager::ConfigManager;
+use crate::constants::database;
+
+/// 默认缩略图尺寸（像素）
+pub const DEFAULT_THUMBNAIL_SIZE: u32 = 64;
+
+/// 允许的最大缩略图尺寸（像素）
+const MAX_THUMBNAIL_SIZE: u32 = 512;
+
+/// 内存中的缩略图缓存项
+#[derive(Clone)]
+struct CachedThumbnail {
+    /// 生成缓存时备份文件的修改时间
+    modified: SystemTime,
+    thumbnail: Option<(PathBuf, ImageFormat)>,
+    data_url: Option<String>,
+}
+
+/// 缩略图缓存键：账户 ID、备份文件路径和缩略图尺寸
+type ThumbnailKey = (String, PathBuf, u32);
+
+/// 缩略图缓存，备份文件未修改时不再重新读取和解码备份
+static THUMBNAIL_CACHE: Lazy<Mutex<HashMap<ThumbnailKey, CachedThumbnail>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 账户头像信息
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountAvatar {
+    pub account_id: String,
+    pub email: String,
+    /// 原始头像的图片格式（如 png / jpeg / webp）
+    pub format: String,
+    /// 缩略图缓存文件路径
+    pub thumbnail_path: String,
+    /// 缩略图的 data URL（仅在请求时生成）
+    pub data_url: Option<String>,
+}
+
+/// 解码 profileUrl 的值
+///
+/// 支持以下几种存储形式：
+/// - `data:image/png;base64,...` 形式的 data URL
+/// - 纯 Base64 字符串（标准或 URL 安全字母表）
+/// - 以上两种被 JSON 字符串再包一层的情况
+///
+/// 远程 URL（http/https）无法离线解码，返回 `None`
+pub fn decode_profile_url(raw: &str) -> Option<Vec<u8>> {
+    let value = serde_json::from_str::<String>(raw).unwrap_or_else(|_| raw.to_string());
+    let value = value.trim();
+
+    if value.is_empty() || value.starts_with("http://") || value.starts_with("https://") {
+        return None;
+    }
+
+    let payload = match value.strip_prefix("data:") {
+        Some(rest) => rest.split_once(";base64,")?.1,
+        None => value,
+    };
+    let payload: String = payload.chars().filter(|c| !c.is_whitespace()).collect();
+
+    base64::engine::general_purpose::STANDARD
+        .decode(&payload)
+        .or_else(|_| base64::engine::general_purpose::STANDARD_NO_PAD.decode(&payload))
+        .or_else(|_| base64::engine::general_purpose::URL_SAFE.decode(&payload))
+        .or_else(|_| base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(&payload))
+        .ok()
+}
+
+/// 从账户备份文件中读取并解码头像
+///
+/// 返回解码后的图片数据和检测到的图片格式
+fn read_avatar_from_backup(backup_file: &Path) -> Result<Option<(Vec<u8>, ImageFormat)>, String> {
+    let content = fs::read_to_string(backup_file)
+        .map_err(|e| format!("读取备份 {} 失败: {}", backup_file.display(), e))?;
+    let backup: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("解析备份 {} 失败: {}", backup_file.display(), e))?;
+
+    let Some(raw) = backup.get(database::PROFILE_URL).and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+
+    let Some(bytes) = decode_profile_url(raw) else {
+        return Ok(None);
+    };
+
+    match image::guess_format(&bytes) {
+        Ok(format) => Ok(Some((bytes, format))),
+        Err(e) => {
+            log::warn!("⚠️ 无法识别头像图片格式 ({}): {}", backup_file.display(), e);
+            Ok(None)
+        }
+    }
+}
+
+/// 获取头像缩略图缓存目录
+fn avatar_cache_dir() -> Result<PathBuf, String> {
+    ConfigManager::new()?.avatar_cache_dir()
+}
+
+/// 生成（或复用缓存的）账户头像缩略图
+///
+/// 缓存文件名包含原图内容的哈希，头像变化后会自动生成新的缩略图并清理旧文件
+pub fn ensure_thumbnail(
+    entry: &AccountEntry,
+    backup_file: &Path,
+    size: u32,
+) -> Result<Option<(PathBuf, ImageFormat)>, String> {
+    let size = size.clamp(16, MAX_THUMBNAIL_SIZE);
+
+    let Some((bytes, format)) = read_avatar_from_backup(backup_file)? else {
+        return Ok(None);
+    };
+
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    let prefix = format!("{}_{}_", entry.id, size);
+    let file_name = format!("{}{:016x}.png", prefix, hasher.finish());
+
+    let cache_dir = avatar_cache_dir()?;
+    let thumbnail_path = account_registry::safe_join(&cache_dir, &file_name)?;
+    if thumbnail_path.exists() {
+        return Ok(Some((thumbnail_path, format)));
+    }
+
+    // 清理同一账户、同一尺寸的旧缩略图
+    if let Ok(entries) = fs::read_dir(&cache_dir) {
+        for old in entries.flatten() {
+            if old.file_name().to_string_lossy().starts_with(&prefix) {
+                let _ = fs::remove_file(old.path());
+            }
+        }
+    }
+
+    let image = image::load_from_memory_with_format(&bytes, format)
+        .map_err(|e| format!("解码头像失败: {}", e))?;
+    image
+        .resize_to_fill(size, size, FilterType::Lanczos3)
+        .save_with_format(&thumbnail_path, ImageFormat::Png)
+        .map_err(|e| format!("保存头像缩略图失败: {}", e))?;
+
+    println!(
+        "🖼️ 已生成头像缩略图: {} ({}x{})",
+        thumbnail_path.display(),
+        size,
+        size
+    );
+    Ok(Some((thumbnail_path, format)))
+}
+
+/// 将缩略图文件编码为 PNG data URL
+pub fn thumbnail_data_url(thumbnail_path: &Path) -> Result<String, String> {
+    let bytes = fs::read(thumbnail_path).map_err(|e| format!("读取头像缩略图失败: {}", e))?;
+    Ok(format!(
+        "data:{};base64,{}",
+        ImageFormat::Png.to_mime_type(),
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+/// 获取单个账户的头像信息
+pub fn get_account_avatar(
+    entry: &AccountEntry,
+    backup_file: &Path,
+    size: u32,
+    with_data_url: bool,
+) -> Result<Option<AccountAvatar>, String> {
+    let key = (entry.id.clone(), backup_file.to_path_buf(), size);
+    let modified = fs::metadata(backup_file)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("读取备份 {} 失败: {}", backup_file.display(), e))?;
+
+    // 备份文件未修改且缩略图文件仍存在时直接使用缓存
+    let cached = THUMBNAIL_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&key)
+        .filter(|c| {
+            c.modified == modified && c.thumbnail.as_ref().is_none_or(|(path, _)| path.exists())
+        })
+        .cloned();
+    let mut cached = match cached {
+        Some(cached) => cached,
+        None => CachedThumbnail {
+            modified,
+            thumbnail: ensure_thumbnail(entry, backup_file, size)?,
+            data_url: None,
+        },
+    };
+
+    let Some((thumbnail_path, format)) = cached.thumbnail.clone() else {
+        THUMBNAIL_CACHE
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, cached);
+        return Ok(None);
+    };
+
+    if with_data_url && cached.data_url.is_none() {
+        cached.data_url = Some(thumbnail_data_url(&thumbnail_path)?);
+    }
+    let data_url = if with_data_url {
+        cached.data_url.clone()
+    } else {
+        None
+    };
+    THUMBNAIL_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key, cached);
+
+    Ok(Some(AccountAvatar {
+        account_id: entry.id.clone(),
+        email: entry.email.clone(),
+        format: format
+            .extensions_str()
+            .first()
+            .copied()
+            .unwrap_or("unknown")
+            .to_string(),
+        thumbnail_path: thumbnail_path.to_string_lossy().to_string(),
+        data_url,
+    }))
+}
+
+/// 获取所有账户的头像信息，单个账户失败不影响其他账户
+pub fn get_all_account_avatars(
+    size: u32,
+    with_data_url: bool,
+) -> Result<Vec<AccountAvatar>, String> {
+    let registry = account_registry::load_registry()?;
+    let accounts_dir = ConfigManager::new()?.accounts_dir()?;
+
+    let mut avatars = Vec::new();
+    for entry in &registry.accounts {
+        let backup_file = account_registry::backup_file_path(&accounts_dir, entry)?;
+        match get_account_avatar(entry, &backup_file, size, with_data_url) {
+            Ok(Some(avatar)) => avatars.push(avatar),
+            Ok(None) => {}
+            Err(e) => log::warn!("⚠️ 处理账户 {} 的头像失败: {}", entry.email, e),
+        }
+    }
+
+    Ok(avatars)
+}
@@ -0,0 +1,1043 @@
+// Antigravity 账户注册表模块
+// 为每个账户分配稳定的不透明 ID，并维护 ID / 邮箱 / 别名 与备份文件之间的映射
+
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use crate::config_manager::ConfigManager;
+
+/// 注册表文件格式版本
+const REGISTRY_VERSION: u32 = 1;
+
+/// 备份文件名（不含扩展名）的最大长度
+const MAX_FILE_STEM_LEN: usize = 96;
+
+/// 切换历史最多保留的记录数
+const MAX_SWITCH_HISTORY: usize = 100;
+
+/// Windows 保留的设备名，不能作为文件名使用
+const RESERVED_FILE_STEMS: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// 备份文件中记录账户 ID 的元信息字段
+pub const ACCOUNT_ID_FIELD: &str = "account_id";
+
+/// 备份文件中记录账户邮箱的元信息字段
+pub const ACCOUNT_EMAIL_FIELD: &str = "account_email";
+
+/// 注册表变化事件（账户新增、删除、重命名或当前账户变化后发送）
+pub const REGISTRY_CHANGED_EVENT: &str = "account-registry://changed";
+
+/// 注册表读写锁，保证“加载 - 修改 - 保存”串行执行
+static REGISTRY_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// 用于发送注册表变化事件的应用句柄
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+
+/// 注册表中的单个账户
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountEntry {
+    /// 稳定的不透明 ID，与邮箱和文件名无关
+    pub id: String,
+    /// 账户邮箱（保留原始大小写，比较时忽略大小写）
+    pub email: String,
+    /// 备份文件名（不含 `.json` 扩展名），已经过清洗
+    pub file_name: String,
+    /// 用户自定义别名
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub created_at: String,
+    #[serde(default)]
+    pub last_switched: Option<String>,
+    /// 已被用户停用，轮换时跳过
+    #[serde(default)]
+    pub disabled: bool,
+    /// 凭据已过期，轮换时跳过
+    #[serde(default)]
+    pub expired: bool,
+    /// 冷却截止时间（触发配额、限流或认证错误后），冷却期间轮换时跳过
+    #[serde(default)]
+    pub cooldown_until: Option<String>,
+    /// 进入冷却的原因
+    #[serde(default)]
+    pub cooldown_reason: Option<String>,
+    /// 备份文件已不存在（保留 ID、别名、轮换顺序等信息，文件重新出现时自动恢复）
+    #[serde(default)]
+    pub orphaned: bool,
+}
+
+impl AccountEntry {
+    /// 是否处于冷却期
+    pub fn in_cooldown(&self) -> bool {
+        self.cooldown_until
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .is_some_and(|until| until > chrono::Local::now())
+    }
+
+    /// 是否可以参与轮换
+    pub fn is_available(&self) -> bool {
+        !self.orphaned && !self.disabled && !self.expired && !self.in_cooldown()
+    }
+
+    /// 检查给定的查找键是否指向当前账户
+    fn matches(&self, key: &str) -> bool {
+        self.id == key
+            || self.file_name == key
+            || self.email.eq_ignore_ascii_case(key)
+            || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(key))
+    }
+}
+
+/// 一次账户切换记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchRecord {
+    pub account_id: String,
+    pub switched_at: String,
+}
+
+/// 账户注册表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountRegistry {
+    pub version: u32,
+    #[serde(default)]
+    pub accounts: Vec<AccountEntry>,
+    /// 当前已恢复到 Antigravity 中的账户 ID
+    #[serde(default)]
+    pub current_account_id: Option<String>,
+    /// 切换历史（按时间顺序，最新的在最后）
+    #[serde(default)]
+    pub switch_history: Vec<SwitchRecord>,
+    /// 用户自定义的轮换顺序（账户 ID），为空时按注册顺序轮换全部账户
+    #[serde(default)]
+    pub rotation_order: Vec<String>,
+}
+
+/// 轮换方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationDirection {
+    /// 轮换顺序中的下一个账户
+    Next,
+    /// 轮换顺序中的上一个账户
+    Previous,
+    /// 切换历史中上一次使用的账户
+    Last,
+}
+
+impl Default for AccountRegistry {
+    fn default() -> Self {
+        Self {
+            version: REGISTRY_VERSION,
+            accounts: Vec::new(),
+            current_account_id: None,
+            switch_history: Vec::new(),
+            rotation_order: Vec::new(),
+        }
+    }
+}
+
+impl AccountRegistry {
+    /// 按 ID、文件名、邮箱或别名查找账户
+    ///
+    /// 优先精确匹配 ID，避免别名与其他账户的 ID 冲突时解析到错误账户
+    pub fn resolve(&self, key: &str) -> Option<&AccountEntry> {
+        self.accounts
+            .iter()
+            .find(|a| a.id == key)
+            .or_else(|| self.accounts.iter().find(|a| a.matches(key)))
+    }
+
+    /// 按查找键定位账户在列表中的位置
+    fn position(&self, key: &str) -> Result<usize, String> {
+        let id = self
+            .resolve(key)
+            .map(|a| a.id.clone())
+            .ok_or_else(|| format!("未找到账户: {}", key))?;
+        Ok(self
+            .accounts
+            .iter()
+            .position(|a| a.id == id)
+            .unwrap_or_default())
+    }
+
+    /// 检查查找键是否已被其他账户占用（ID、文件名、邮箱或别名）
+    fn key_in_use(&self, key: &str, except_id: &str) -> bool {
+        self.accounts
+            .iter()
+            .any(|a| a.id != except_id && a.matches(key))
+    }
+
+    /// 按邮箱查找账户（忽略大小写）
+    pub fn find_by_email(&self, email: &str) -> Option<&AccountEntry> {
+        self.accounts
+            .iter()
+            .find(|a| a.email.eq_ignore_ascii_case(email))
+    }
+
+    /// 获取邮箱对应的账户，不存在时注册一个新账户
+    pub fn ensure_account(&mut self, email: &str) -> Result<AccountEntry, String> {
+        validate_account_key(email)?;
+
+        if let Some(entry) = self.find_by_email(email) {
+            return Ok(entry.clone());
+        }
+
+        let entry = AccountEntry {
+            id: self.generate_id(),
+            email: email.to_string(),
+            file_name: self.unique_file_name(email, None),
+            aliases: Vec::new(),
+            created_at: chrono::Local::now().to_rfc3339(),
+            last_switched: None,
+            disabled: false,
+            expired: false,
+            cooldown_until: None,
+            cooldown_reason: None,
+            orphaned: false,
+        };
+        log::info!("🆕 注册新账户: {} (ID: {})", entry.email, entry.id);
+        self.accounts.push(entry.clone());
+        Ok(entry)
+    }
+
+    /// 按 ID 移除账户
+    pub fn remove(&mut self, id: &str) -> Option<AccountEntry> {
+        let index = self.accounts.iter().position(|a| a.id == id)?;
+        if self.current_account_id.as_deref() == Some(id) {
+            self.current_account_id = None;
+        }
+        self.rotation_order.retain(|r| r != id);
+        Some(self.accounts.remove(index))
+    }
+
+    /// 将账户标记为当前账户，更新最后切换时间并记录切换历史
+    pub fn mark_switched(&mut self, id: &str) -> Result<AccountEntry, String> {
+        let entry = self
+            .accounts
+            .iter_mut()
+            .find(|a| a.id == id)
+            .ok_or_else(|| format!("未找到账户: {}", id))?;
+        entry.last_switched = Some(chrono::Local::now().to_rfc3339());
+        let entry = entry.clone();
+
+        self.current_account_id = Some(entry.id.clone());
+        self.record_switch(&entry.id);
+        Ok(entry)
+    }
+
+    /// 记录一次切换
+    fn record_switch(&mut self, id: &str) {
+        self.switch_history.push(SwitchRecord {
+            account_id: id.to_string(),
+            switched_at: chrono::Local::now().to_rfc3339(),
+        });
+
+        let overflow = self.switch_history.len().saturating_sub(MAX_SWITCH_HISTORY);
+        self.switch_history.drain(..overflow);
+    }
+
+    /// 重命名账户的备份文件（账户 ID 保持不变）
+    pub fn rename(
+        &mut self,
+        accounts_dir: &Path,
+        key: &str,
+        new_name: &str,
+    ) -> Result<AccountEntry, String> {
+        validate_account_key(new_name)?;
+        let index = self.position(key)?;
+        let id = self.accounts[index].id.clone();
+
+        if self.key_in_use(new_name, &id) {
+            return Err(format!("名称已被其他账户使用: {}", new_name));
+        }
+
+        let new_file_name = self.unique_file_name(new_name, Some(&id));
+        let old_path = backup_file_path(accounts_dir, &self.accounts[index])?;
+        let new_path = safe_join(accounts_dir, &format!("{}.json", new_file_name))?;
+
+        if old_path != new_path {
+            fs::rename(&old_path, &new_path).map_err(|e| format!("重命名备份文件失败: {}", e))?;
+        }
+
+        let entry = &mut self.accounts[index];
+        log::info!(
+            "✏️ 账户重命名: {} -> {} (ID: {})",
+            entry.file_name,
+            new_file_name,
+            entry.id
+        );
+        entry.file_name = new_file_name;
+        Ok(entry.clone())
+    }
+
+    /// 为账户添加别名
+    pub fn add_alias(&mut self, key: &str, alias: &str) -> Result<AccountEntry, String> {
+        validate_account_key(alias)?;
+        let alias = alias.trim();
+        let index = self.position(key)?;
+
+        if self.key_in_use(alias, &self.accounts[index].id) {
+            return Err(format!("别名已被其他账户使用: {}", alias));
+        }
+
+        let entry = &mut self.accounts[index];
+        if !entry.aliases.iter().any(|a| a.eq_ignore_ascii_case(alias)) {
+            entry.aliases.push(alias.to_string());
+        }
+        Ok(entry.clone())
+    }
+
+    /// 移除账户的别名
+    pub fn remove_alias(&mut self, key: &str, alias: &str) -> Result<AccountEntry, String> {
+        let index = self.position(key)?;
+        let entry = &mut self.accounts[index];
+
+        let before = entry.aliases.len();
+        entry.aliases.retain(|a| !a.eq_ignore_ascii_case(alias));
+        if entry.aliases.len() == before {
+            return Err(format!("账户 {} 没有别名: {}", entry.email, alias));
+        }
+        Ok(entry.clone())
+    }
+
+    /// 合并同一身份的两个备份
+    ///
+    /// - 按 `backup_time` 保留较新的认证数据，较旧备份中独有的字段会被保留
+    /// - 保留 `primary_key` 对应账户的 ID，另一个账户的文件名和别名转为别名
+    /// - 切换历史中指向被合并账户的记录会改为指向保留的账户
+    pub fn merge(
+        &mut self,
+        accounts_dir: &Path,
+        primary_key: &str,
+        duplicate_key: &str,
+    ) -> Result<AccountEntry, String> {
+        let primary_index = self.position(primary_key)?;
+        let duplicate_index = self.position(duplicate_key)?;
+        if primary_index == duplicate_index {
+            return Err("不能将账户与自身合并".to_string());
+        }
+
+        let primary = self.accounts[primary_index].clone();
+        let duplicate = self.accounts[duplicate_index].clone();
+        if !primary.email.eq_ignore_ascii_case(&duplicate.email) {
+            return Err(format!(
+                "只能合并同一身份的备份: {} 与 {}",
+                primary.email, duplicate.email
+            ));
+        }
+
+        let primary_path = backup_file_path(accounts_dir, &primary)?;
+        let duplicate_path = backup_file_path(accounts_dir, &duplicate)?;
+        let primary_data = read_backup_object(&primary_path)?;
+        let duplicate_data = read_backup_object(&duplicate_path)?;
+
+        // 以较新的备份为准，较旧备份只补充缺失的字段
+        let (mut merged, newer) = if backup_time(&duplicate_data) > backup_time(&primary_data) {
+            (primary_data, duplicate_data)
+        } else {
+            (duplicate_data, primary_data)
+        };
+        let newer_email = newer
+            .get(ACCOUNT_EMAIL_FIELD)
+            .and_then(|v| v.as_str())
+            .unwrap_or(&primary.email)
+            .to_string();
+        merged.extend(newer);
+        merged.insert(
+            ACCOUNT_ID_FIELD.to_string(),
+            Value::String(primary.id.clone()),
+        );
+        merged.insert(
+            ACCOUNT_EMAIL_FIELD.to_string(),
+            Value::String(newer_email.clone()),
+        );
+
+        // 先写临时文件再替换，替换成功后才删除重复备份，避免写入失败时两份备份都丢失
+        let content =
+            serde_json::to_string_pretty(&merged).map_err(|e| format!("序列化备份失败: {}", e))?;
+        let tmp_path = primary_path.with_extension("json.tmp");
+        fs::write(&tmp_path, content).map_err(|e| format!("写入合并后的备份失败: {}", e))?;
+        if let Err(e) = fs::rename(&tmp_path, &primary_path) {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(format!("替换合并后的备份失败: {}", e));
+        }
+        fs::remove_file(&duplicate_path).map_err(|e| format!("删除重复备份失败: {}", e))?;
+
+        // 合并元信息
+        let duplicate_was_current = self.current_account_id.as_deref() == Some(&duplicate.id);
+        self.remove(&duplicate.id);
+        if duplicate_was_current {
+            self.current_account_id = Some(primary.id.clone());
+        }
+        let entry = self
+            .accounts
+            .iter_mut()
+            .find(|a| a.id == primary.id)
+            .ok_or_else(|| format!("未找到账户: {}", primary.id))?;
+
+        entry.email = newer_email;
+        for alias in duplicate
+            .aliases
+            .iter()
+            .chain(std::iter::once(&duplicate.file_name))
+        {
+            if !alias.eq_ignore_ascii_case(&entry.file_name)
+                && !entry.aliases.iter().any(|a| a.eq_ignore_ascii_case(alias))
+            {
+                entry.aliases.push(alias.clone());
+            }
+        }
+        entry.created_at = std::cmp::min(entry.created_at.clone(), duplicate.created_at);
+        entry.last_switched = std::cmp::max(entry.last_switched.clone(), duplicate.last_switched);
+        let merged_entry = entry.clone();
+
+        for record in &mut self.switch_history {
+            if record.account_id == duplicate.id {
+                record.account_id = primary.id.clone();
+            }
+        }
+        let mut seen = Vec::new();
+        for id in &mut self.rotation_order {
+            if *id == duplicate.id {
+                *id = primary.id.clone();
+            }
+        }
+        self.rotation_order.retain(|id| {
+            let first = !seen.contains(id);
+            seen.push(id.clone());
+            first
+        });
+
+        log::info!(
+            "🔗 已合并账户: {} -> {} (ID: {})",
+            duplicate.file_name,
+            merged_entry.file_name,
+            merged_entry.id
+        );
+        Ok(merged_entry)
+    }
+
+    /// 设置账户的停用 / 过期标记
+    pub fn set_flags(
+        &mut self,
+        key: &str,
+        disabled: Option<bool>,
+        expired: Option<bool>,
+    ) -> Result<AccountEntry, String> {
+        let index = self.position(key)?;
+        let entry = &mut self.accounts[index];
+        if let Some(disabled) = disabled {
+            entry.disabled = disabled;
+        }
+        if let Some(expired) = expired {
+            entry.expired = expired;
+        }
+        log::info!(
+            "🏷️ 更新账户标记: {} (停用: {}, 过期: {})",
+            entry.email,
+            entry.disabled,
+            entry.expired
+        );
+        Ok(entry.clone())
+    }
+
+    /// 设置账户的冷却截止时间，`until` 为空时清除冷却
+    pub fn set_cooldown(
+        &mut self,
+        key: &str,
+        until: Option<chrono::DateTime<chrono::Local>>,
+        reason: Option<String>,
+    ) -> Result<AccountEntry, String> {
+        let index = self.position(key)?;
+        let entry = &mut self.accounts[index];
+        entry.cooldown_until = until.map(|t| t.to_rfc3339());
+        entry.cooldown_reason = until.and(reason);
+        Ok(entry.clone())
+    }
+
+    /// 设置轮换顺序，`keys` 支持账户 ID / 邮箱 / 别名 / 文件名
+    pub fn set_rotation_order(&mut self, keys: &[String]) -> Result<Vec<AccountEntry>, String> {
+        let mut order: Vec<String> = Vec::new();
+        for key in keys {
+            let id = self.accounts[self.position(key)?].id.clone();
+            if order.contains(&id) {
+                return Err(format!("轮换顺序中账户重复: {}", key));
+            }
+            order.push(id);
+        }
+
+        self.rotation_order = order;
+        Ok(self.rotation().into_iter().cloned().collect())
+    }
+
+    /// 按轮换顺序排列的账户（包括已停用和已过期的账户）
+    pub fn rotation(&self) -> Vec<&AccountEntry> {
+        if self.rotation_order.is_empty() {
+            return self.accounts.iter().collect();
+        }
+
+        self.rotation_order
+            .iter()
+            .filter_map(|id| self.accounts.iter().find(|a| &a.id == id))
+            .collect()
+    }
+
+    /// 计算按指定方向轮换时的目标账户，跳过已停用、已过期和处于冷却中的账户
+    pub fn rotation_target(&self, direction: RotationDirection) -> Result<AccountEntry, String> {
+        let current = self.current_account_id.as_deref();
+
+        if direction == RotationDirection::Last {
+            let last_id = self
+                .switch_history
+                .iter()
+                .rev()
+                .map(|record| record.account_id.as_str())
+                .find(|id| Some(*id) != current && self.accounts.iter().any(|a| a.id == *id))
+                .ok_or("切换历史中没有可以切换回去的账户")?;
+            let entry = self.resolve(last_id).ok_or("未找到上一次使用的账户")?;
+            if !entry.is_available() {
+                return Err(format!(
+                    "上一次使用的账户 {} 已停用、已过期或处于冷却中",
+                    entry.email
+                ));
+            }
+            return Ok(entry.clone());
+        }
+
+        let rotation = self.rotation();
+        if rotation.is_empty() {
+            return Err("轮换列表中没有账户".to_string());
+        }
+
+        let len = rotation.len();
+        let start = current.and_then(|id| rotation.iter().position(|a| a.id == id));
+        // 当前账户不在轮换列表中时，“下一个”从第一个开始，“上一个”从最后一个开始
+        let candidates = (1..=len).map(|offset| match (direction, start) {
+            (RotationDirection::Previous, Some(start)) => (start + len - offset) % len,
+            (RotationDirection::Previous, None) => len - offset,
+            (_, Some(start)) => (start + offset) % len,
+            (_, None) => offset - 1,
+        });
+
+        for index in candidates {
+            let entry = rotation[index];
+            if Some(entry.id.as_str()) != current && entry.is_available() {
+                return Ok(entry.clone());
+            }
+        }
+
+        Err("轮换列表中没有其他可用的账户".to_string())
+    }
+
+    /// 找出邮箱相同（忽略大小写）的重复账户分组
+    pub fn duplicate_groups(&self) -> Vec<Vec<AccountEntry>> {
+        let mut groups: Vec<Vec<AccountEntry>> = Vec::new();
+        for entry in &self.accounts {
+            match groups
+                .iter_mut()
+                .find(|g| g[0].email.eq_ignore_ascii_case(&entry.email))
+            {
+                Some(group) => group.push(entry.clone()),
+                None => groups.push(vec![entry.clone()]),
+            }
+        }
+        groups.retain(|g| g.len() > 1);
+        groups
+    }
+
+    /// 生成一个注册表内唯一的账户 ID
+    fn generate_id(&self) -> String {
+        loop {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u128(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos(),
+            );
+            let id = format!("acc_{:016x}", hasher.finish());
+            if !self.accounts.iter().any(|a| a.id == id) {
+                return id;
+            }
+        }
+    }
+
+    /// 生成一个不与其他账户冲突的文件名
+    ///
+    /// 比较时忽略大小写，以兼容大小写不敏感的文件系统
+    fn unique_file_name(&self, name: &str, except_id: Option<&str>) -> String {
+        let base = sanitize_file_stem(name);
+        let taken = |name: &str| {
+            self.accounts
+                .iter()
+                .any(|a| Some(a.id.as_str()) != except_id && a.file_name.eq_ignore_ascii_case(name))
+        };
+
+        if !taken(&base) {
+            return base;
+        }
+
+        (2..)
+            .map(|n| format!("{}-{}", base, n))
+            .find(|name| !taken(name))
+            .unwrap_or(base)
+    }
+
+    /// 与账户目录中的实际备份文件同步
+    ///
+    /// - 目录中存在但未登记的备份文件会被注册（兼容旧版本直接以邮箱命名的文件）
+    /// - 备份文件已不存在的账户标记为孤立，不删除其元信息；文件重新出现时恢复
+    /// - 目录无法读取时不做任何修改，避免临时的读取错误清空注册表
+    ///
+    /// 返回注册表是否发生变化
+    fn sync_with_dir(&mut self, accounts_dir: &Path) -> bool {
+        let entries = match fs::read_dir(accounts_dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!("⚠️ 读取账户目录失败，跳过注册表同步: {}", e);
+                return false;
+            }
+        };
+
+        let mut stems = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    stems.push(stem.to_string());
+                }
+            }
+        }
+
+        let mut changed = false;
+        for account in &mut self.accounts {
+            let orphaned = !stems.contains(&account.file_name);
+            if account.orphaned != orphaned {
+                if orphaned {
+                    log::warn!(
+                        "⚠️ 账户 {} 的备份文件 {}.json 不存在，已标记为孤立",
+                        account.email,
+                        account.file_name
+                    );
+                } else {
+                    log::info!("📋 账户 {} 的备份文件已恢复", account.email);
+                }
+                account.orphaned = orphaned;
+                changed = true;
+            }
+        }
+
+        for stem in stems {
+            if self.accounts.iter().any(|a| a.file_name == stem) {
+                continue;
+            }
+
+            // 读取备份中的元信息，优先沿用文件中记录的 ID 和邮箱
+            let meta = fs::read_to_string(accounts_dir.join(format!("{}.json", stem)))
+                .ok()
+                .and_then(|content| serde_json::from_str::<Value>(&content).ok());
+            let meta_str = |field: &str| {
+                meta.as_ref()
+                    .and_then(|m| m.get(field))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            };
+
+            // 孤立账户的备份文件以新文件名重新出现时，沿用原有的账户信息
+            let file_id = meta_str(ACCOUNT_ID_FIELD);
+            if let Some(account) = self
+                .accounts
+                .iter_mut()
+                .find(|a| a.orphaned && file_id.as_deref() == Some(a.id.as_str()))
+            {
+                log::info!("📋 账户 {} 的备份文件已恢复: {}.json", account.email, stem);
+                account.file_name = stem;
+                account.orphaned = false;
+                changed = true;
+                continue;
+            }
+
+            let id = file_id
+                .filter(|id| !self.accounts.iter().any(|a| &a.id == id))
+                .unwrap_or_else(|| self.generate_id());
+            let email = meta_str(ACCOUNT_EMAIL_FIELD).unwrap_or_else(|| stem.clone());
+
+            log::info!("📋 登记已有备份文件: {}.json (ID: {})", stem, id);
+            self.accounts.push(AccountEntry {
+                id,
+                email,
+                file_name: stem,
+                aliases: Vec::new(),
+                created_at: meta_str("backup_time")
+                    .unwrap_or_else(|| chrono::Local::now().to_rfc3339()),
+                last_switched: None,
+                disabled: false,
+                expired: false,
+                cooldown_until: None,
+                cooldown_reason: None,
+                orphaned: false,
+            });
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// 从磁盘读取注册表，文件不存在或损坏时返回空注册表
+    fn read_from(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<Self>(&content).map_err(|e| e.to_string()))
+        {
+            Ok(registry) => registry,
+            Err(e) => {
+                log::warn!("⚠️ 账户注册表损坏，将根据备份文件重建: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// 写入注册表（先写临时文件再替换，避免写入中断导致文件损坏）
+    fn write_to(&self, path: &Path) -> Result<(), String> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| format!("序列化注册表失败: {}", e))?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, content).map_err(|e| format!("写入注册表失败: {}", e))?;
+        fs::rename(&tmp_path, path).map_err(|e| format!("替换注册表文件失败: {}", e))
+    }
+}
+
+/// 读取备份文件中的 JSON 对象
+fn read_backup_object(path: &Path) -> Result<serde_json::Map<String, Value>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("读取备份 {} 失败: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析备份 {} 失败: {}", path.display(), e))
+}
+
+/// 解析备份中的 `backup_time`，缺失或无法解析时视为最旧
+fn backup_time(
+    data: &serde_json::Map<String, Value>,
+) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    data.get("backup_time")
+        .and_then(|v| v.as_str())
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+}
+
+/// 校验调用方传入的账户名 / ID / 邮箱 / 别名
+///
+/// 拒绝空值、路径分隔符、`..` 和控制字符，防止路径穿越
+pub fn validate_account_key(key: &str) -> Result<(), String> {
+    if key.trim().is_empty() {
+        return Err("账户名不能为空".to_string());
+    }
+
+    if key.contains(['/', '\\']) || key.contains("..") || key.chars().any(|c| c.is_control()) {
+        return Err(format!("非法的账户名: {}", key));
+    }
+
+    Ok(())
+}
+
+/// 将任意字符串清洗为安全的文件名（不含扩展名）
+///
+/// 仅保留字母、数字和 `@._+-`，其余字符替换为 `_`，并统一转为小写
+pub fn sanitize_file_stem(raw: &str) -> String {
+    let cleaned: String = raw
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '@' | '.' | '_' | '+' | '-') {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_FILE_STEM_LEN)
+        .collect();
+
+    // 去掉首尾的点，避免隐藏文件以及 Windows 下的尾随点问题
+    let mut stem = cleaned.trim_matches('.').to_string();
+    while stem.contains("..") {
+        stem = stem.replace("..", ".");
+    }
+
+    if stem.is_empty() {
+        return "account".to_string();
+    }
+
+    let device_name = stem.split('.').next().unwrap_or_default();
+    if RESERVED_FILE_STEMS.contains(&device_name) {
+        stem.insert(0, '_');
+    }
+
+    stem
+}
+
+/// 在目录下安全地拼接文件名
+///
+/// 文件名必须是单个普通路径组件，否则返回错误
+pub fn safe_join(dir: &Path, file_name: &str) -> Result<PathBuf, String> {
+    validate_account_key(file_name)?;
+
+    let mut components = Path::new(file_name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) if name == file_name => Ok(dir.join(name)),
+        _ => Err(format!("非法的文件名: {}", file_name)),
+    }
+}
+
+/// 获取账户目录与注册表文件路径
+fn registry_paths() -> Result<(PathBuf, PathBuf), String> {
+    let config_manager = ConfigManager::new()?;
+    Ok((
+        config_manager.accounts_dir()?,
+        config_manager.account_registry_file(),
+    ))
+}
+
+/// 记录应用句柄，用于发送注册表变化事件
+pub fn init(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+/// 发送注册表变化事件（需要在释放注册表锁之后调用）
+fn emit_changed() {
+    if let Some(app) = APP_HANDLE.get() {
+        if let Err(e) = app.emit(REGISTRY_CHANGED_EVENT, ()) {
+            log::warn!("⚠️ 发送注册表变化事件失败: {}", e);
+        }
+    }
+}
+
+/// 在持有注册表锁的情况下加载、同步并修改注册表，最后写回磁盘并发送变化事件
+pub fn update_registry<T>(
+    f: impl FnOnce(&mut AccountRegistry, &Path) -> Result<T, String>,
+) -> Result<T, String> {
+    let result = {
+        let _guard = REGISTRY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let (accounts_dir, registry_file) = registry_paths()?;
+
+        let mut registry = AccountRegistry::read_from(&registry_file);
+        registry.sync_with_dir(&accounts_dir);
+
+        let result = f(&mut registry, &accounts_dir)?;
+        registry.version = REGISTRY_VERSION;
+        registry.write_to(&registry_file)?;
+        result
+    };
+
+    emit_changed();
+    Ok(result)
+}
+
+/// 加载注册表（会与账户目录同步）
+pub fn load_registry() -> Result<AccountRegistry, String> {
+    let _guard = REGISTRY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let (accounts_dir, registry_file) = registry_paths()?;
+
+    let mut registry = AccountRegistry::read_from(&registry_file);
+    if registry.sync_with_dir(&accounts_dir) || !registry_file.exists() {
+        registry.write_to(&registry_file)?;
+    }
+    Ok(registry)
+}
+
+/// 获取账户对应的备份文件路径
+pub fn backup_file_path(accounts_dir: &Path, entry: &AccountEntry) -> Result<PathBuf, String> {
+    safe_join(accounts_dir, &format!("{}.json", entry.file_name))
+}
+
+/// 将 ID / 邮箱 / 别名 / 文件名解析为账户及其备份文件路径
+pub fn resolve_account(key: &str) -> Result<(AccountEntry, PathBuf), String> {
+    validate_account_key(key)?;
+
+    let registry = load_registry()?;
+    let entry = registry
+        .resolve(key)
+        .cloned()
+        .ok_or_else(|| format!("未找到账户: {}", key))?;
+
+    let accounts_dir = ConfigManager::new()?.accounts_dir()?;
+    let path = backup_file_path(&accounts_dir, &entry)?;
+    Ok((entry, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{entry, temp_dir};
+
+    fn registry(ids: &[&str]) -> AccountRegistry {
+        AccountRegistry {
+            accounts: ids
+                .iter()
+                .map(|id| entry(id, &format!("{}@example.com", id)))
+                .collect(),
+            ..AccountRegistry::default()
+        }
+    }
+
+    #[test]
+    fn validate_account_key_rejects_traversal() {
+        assert!(validate_account_key("user@example.com").is_ok());
+        assert!(validate_account_key("").is_err());
+        assert!(validate_account_key("   ").is_err());
+        assert!(validate_account_key("../config").is_err());
+        assert!(validate_account_key("..").is_err());
+        assert!(validate_account_key("a/b").is_err());
+        assert!(validate_account_key("a\\b").is_err());
+        assert!(validate_account_key("a\nb").is_err());
+    }
+
+    #[test]
+    fn sanitize_file_stem_produces_safe_names() {
+        assert_eq!(sanitize_file_stem("User@Example.com"), "user@example.com");
+        assert_eq!(sanitize_file_stem("../../etc/passwd"), "_._etc_passwd");
+        assert_eq!(sanitize_file_stem("a b/c\\d"), "a_b_c_d");
+        assert_eq!(sanitize_file_stem("..."), "account");
+        assert_eq!(sanitize_file_stem(""), "account");
+        assert_eq!(sanitize_file_stem("CON"), "_con");
+        assert_eq!(sanitize_file_stem("nul.txt"), "_nul.txt");
+        assert_eq!(
+            sanitize_file_stem(&"x".repeat(200)).len(),
+            MAX_FILE_STEM_LEN
+        );
+
+        for raw in ["../x", "a/../../b", "..\\..\\c", " .hidden. "] {
+            let stem = sanitize_file_stem(raw);
+            assert!(validate_account_key(&stem).is_ok(), "{} -> {}", raw, stem);
+        }
+    }
+
+    #[test]
+    fn safe_join_only_accepts_single_components() {
+        let dir = Path::new("accounts");
+        assert_eq!(
+            safe_join(dir, "a@example.com.json").unwrap(),
+            dir.join("a@example.com.json")
+        );
+        assert!(safe_join(dir, "../a.json").is_err());
+        assert!(safe_join(dir, "sub/a.json").is_err());
+        assert!(safe_join(dir, "/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn rotation_target_wraps_and_skips_unavailable() {
+        let mut registry = registry(&["a", "b", "c"]);
+        registry.current_account_id = Some("c".to_string());
+        assert_eq!(
+            registry
+                .rotation_target(RotationDirection::Next)
+                .unwrap()
+                .id,
+            "a"
+        );
+
+        registry.accounts[0].disabled = true;
+        assert_eq!(
+            registry
+                .rotation_target(RotationDirection::Next)
+                .unwrap()
+                .id,
+            "b"
+        );
+        assert_eq!(
+            registry
+                .rotation_target(RotationDirection::Previous)
+                .unwrap()
+                .id,
+            "b"
+        );
+
+        registry.accounts[1].orphaned = true;
+        assert!(registry.rotation_target(RotationDirection::Next).is_err());
+    }
+
+    #[test]
+    fn rotation_target_follows_custom_order_and_history() {
+        let mut registry = registry(&["a", "b", "c"]);
+        registry.rotation_order = vec!["c".to_string(), "a".to_string()];
+        registry.current_account_id = Some("c".to_string());
+        assert_eq!(
+            registry
+                .rotation_target(RotationDirection::Next)
+                .unwrap()
+                .id,
+            "a"
+        );
+
+        registry.mark_switched("b").unwrap();
+        registry.mark_switched("c").unwrap();
+        assert_eq!(
+            registry
+                .rotation_target(RotationDirection::Last)
+                .unwrap()
+                .id,
+            "b"
+        );
+    }
+
+    #[test]
+    fn sync_with_dir_keeps_metadata_of_missing_files() {
+        let dir = temp_dir("sync");
+        let mut registry = registry(&["a", "b"]);
+        registry.accounts[0].aliases.push("work".to_string());
+        fs::write(dir.join("b@example.com.json"), "{}").unwrap();
+
+        assert!(registry.sync_with_dir(&dir));
+        assert_eq!(registry.accounts.len(), 2);
+        assert!(registry.accounts[0].orphaned);
+        assert_eq!(registry.accounts[0].aliases, vec!["work".to_string()]);
+        assert!(!registry.accounts[1].orphaned);
+
+        fs::write(dir.join("a@example.com.json"), "{}").unwrap();
+        assert!(registry.sync_with_dir(&dir));
+        assert!(!registry.accounts[0].orphaned);
+        assert!(!registry.sync_with_dir(&dir));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn sync_with_dir_ignores_unreadable_directory() {
+        let dir = temp_dir("missing");
+        let _ = fs::remove_dir_all(&dir);
+        let mut registry = registry(&["a", "b"]);
+
+        assert!(!registry.sync_with_dir(&dir));
+        assert_eq!(registry.accounts.len(), 2);
+        assert!(registry.accounts.iter().all(|a| !a.orphaned));
+    }
+
+    #[test]
+    fn sync_with_dir_reattaches_renamed_backup() {
+        let dir = temp_dir("rename");
+        let mut registry = registry(&["a"]);
+        fs::write(
+            dir.join("renamed.json"),
+            format!(r#"{{"{}": "a"}}"#, ACCOUNT_ID_FIELD),
+        )
+        .unwrap();
+
+        assert!(registry.sync_with_dir(&dir));
+        assert_eq!(registry.accounts.len(), 1);
+        assert_eq!(registry.accounts[0].file_name, "renamed");
+        assert!(!registry.accounts[0].orphaned);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,682 @@
+// Antigravity 账户切换模块
+// 将切换流程建模为显式步骤（预检 -> 关闭 -> 备份当前账户 -> 恢复 -> 校验 -> 启动），
+// 每个步骤通过 Tauri 事件上报状态和耗时，并在步骤之间响应取消请求
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::account_registry::{self, AccountEntry};
+use crate::notifications::NotificationEvent;
+
+/// 步骤进度事件
+pub const SWITCH_PROGRESS_EVENT: &str = "account-switch://progress";
+
+/// 操作完成事件（携带完整报告）
+pub const SWITCH_FINISHED_EVENT: &str = "account-switch://finished";
+
+/// 是否有切换类操作正在执行
+static OPERATION_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 是否收到了取消请求
+static CANCEL_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// 当前登录的账户不在已保存账户中时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownAccountPolicy {
+    /// 弹窗询问是否保存（没有界面时自动保存）
+    #[default]
+    Prompt,
+    /// 自动保存为新账户
+    AutoSave,
+    /// 不保存
+    Skip,
+}
+
+/// 账户切换选项（保存在设置中）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchOptions {
+    /// 切换前是否自动备份当前登录的账户
+    #[serde(default = "default_backup_current")]
+    pub backup_current: bool,
+    /// 当前登录的账户未保存过时的处理方式
+    #[serde(default)]
+    pub unknown_account: UnknownAccountPolicy,
+}
+
+fn default_backup_current() -> bool {
+    true
+}
+
+impl Default for SwitchOptions {
+    fn default() -> Self {
+        Self {
+            backup_current: default_backup_current(),
+            unknown_account: UnknownAccountPolicy::default(),
+        }
+    }
+}
+
+/// 切换流程中的步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchStep {
+    /// 预检：解析目标账户、确认数据库和当前状态
+    Preflight,
+    /// 备份当前登录的账户
+    BackupCurrent,
+    /// 关闭 Antigravity 进程
+    Stop,
+    /// 恢复目标账户数据
+    Restore,
+    /// 清除认证数据（登出）
+    Logout,
+    /// 校验数据库中的登录状态
+    Verify,
+    /// 启动 Antigravity
+    Launch,
+}
+
+impl SwitchStep {
+    /// 关键步骤失败会中止整个流程；启动失败只记录，不影响账户数据已切换的事实
+    fn is_critical(self) -> bool {
+        self != SwitchStep::Launch
+    }
+}
+
+/// 步骤状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Running,
+    Succeeded,
+    Skipped,
+    Failed,
+    Cancelled,
+}
+
+/// 操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    /// 关闭 -> 恢复 -> 重新启动
+    Switch,
+    /// Antigravity 未运行时直接恢复，正在运行时等同于 Switch
+    SwitchInPlace,
+    /// 备份当前账户 -> 登出 -> 重新启动
+    BackupAndRestart,
+}
+
+impl OperationKind {
+    /// 各操作依次执行的步骤
+    fn plan(self) -> &'static [SwitchStep] {
+        use SwitchStep::*;
+        match self {
+            // 在关闭之后备份，确保包含 Antigravity 退出时写回的最新令牌
+            OperationKind::Switch | OperationKind::SwitchInPlace => {
+                &[Preflight, Stop, BackupCurrent, Restore, Verify, Launch]
+            }
+            OperationKind::BackupAndRestart => {
+                &[Preflight, Stop, BackupCurrent, Logout, Verify, Launch]
+            }
+        }
+    }
+}
+
+/// 单个步骤的执行结果
+#[derive(Debug, Clone, Serialize)]
+pub struct StepReport {
+    pub step: SwitchStep,
+    pub status: StepStatus,
+    pub message: String,
+    pub duration_ms: u64,
+}
+
+/// 步骤进度事件载荷
+#[derive(Debug, Clone, Serialize)]
+pub struct SwitchProgress {
+    pub kind: OperationKind,
+    pub target: Option<String>,
+    pub step: SwitchStep,
+    pub status: StepStatus,
+    pub message: String,
+    pub duration_ms: Option<u64>,
+}
+
+/// 整个操作的执行报告
+#[derive(Debug, Clone, Serialize)]
+pub struct SwitchReport {
+    pub kind: OperationKind,
+    pub target: Option<String>,
+    /// 切换后的当前账户（登出类操作为空）
+    pub account: Option<AccountEntry>,
+    pub success: bool,
+    pub cancelled: bool,
+    pub steps: Vec<StepReport>,
+    /// 各步骤消息的简要汇总
+    pub summary: String,
+    pub duration_ms: u64,
+}
+
+impl SwitchReport {
+    /// 转换为结果：成功时返回报告，失败或取消时返回汇总信息
+    ///
+    /// 命令直接返回报告（`success = false` 表示失败），以保留每个步骤的详细结果
+    pub fn into_result(self) -> Result<SwitchReport, String> {
+        if self.success {
+            Ok(self)
+        } else {
+            Err(self.summary)
+        }
+    }
+}
+
+/// 步骤执行结果
+enum StepOutcome {
+    Done(String),
+    Skipped(String),
+}
+
+/// 操作互斥守卫，保证同一时间只有一个切换类操作在执行
+struct OperationGuard;
+
+impl OperationGuard {
+    fn acquire() -> Result<Self, String> {
+        OPERATION_RUNNING
+            .compare_exchange(false, true, SeqCst, SeqCst)
+            .map_err(|_| "已有账户切换操作正在进行，请稍后再试".to_string())?;
+        CANCEL_REQUESTED.store(false, SeqCst);
+        Ok(Self)
+    }
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        CANCEL_REQUESTED.store(false, SeqCst);
+        OPERATION_RUNNING.store(false, SeqCst);
+    }
+}
+
+/// 持有操作锁执行读写账户数据的独立操作（如定时备份），避免与切换类操作同时进行
+pub fn run_exclusive<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    let _guard = OperationGuard::acquire()?;
+    f()
+}
+
+/// `run_exclusive` 的异步版本
+pub async fn run_exclusive_async<T>(
+    f: impl std::future::Future<Output = Result<T, String>>,
+) -> Result<T, String> {
+    let _guard = OperationGuard::acquire()?;
+    f.await
+}
+
+/// 检查是否有切换类操作正在执行
+pub fn is_operation_running() -> bool {
+    OPERATION_RUNNING.load(SeqCst)
+}
+
+/// 请求取消当前操作，将在当前步骤结束后生效
+///
+/// 返回是否有正在执行的操作
+pub fn request_cancel() -> bool {
+    if !is_operation_running() {
+        return false;
+    }
+    CANCEL_REQUESTED.store(true, SeqCst);
+    log::info!("⏹️ 已请求取消账户切换操作");
+    true
+}
+
+/// 执行过程中在步骤之间传递的上下文
+struct SwitchContext {
+    app: Option<AppHandle>,
+    kind: OperationKind,
+    options: SwitchOptions,
+    target_key: Option<String>,
+    target: Option<(AccountEntry, PathBuf)>,
+    live_email: Option<String>,
+    /// 预检时确认的：当前登录的账户未保存过时，切换前是否保存
+    save_live_account: bool,
+    was_running: bool,
+    account: Option<AccountEntry>,
+}
+
+/// 关闭 Antigravity 进程，进程未运行时不视为错误
+pub async fn stop_antigravity() -> Result<String, String> {
+    crate::antigravity_process::terminate_antigravity()
+        .await
+        .map(|report| report.summary())
+        .map_err(|e| format!("关闭进程时发生错误: {}", e))
+}
+
+/// 将账户数据恢复到 Antigravity 数据库，并把它标记为当前账户
+///
+/// `key` 支持账户 ID / 邮箱 / 别名 / 文件名
+pub async fn restore_account(key: &str) -> Result<(AccountEntry, String), String> {
+    let (entry, backup_file) = account_registry::resolve_account(key)?;
+    log::info!("📋 解析到账户: {} (ID: {})", entry.email, entry.id);
+
+    let restore_result =
+        crate::antigravity_restore::restore_all_antigravity_data(backup_file).await?;
+    crate::utils::log_decorator::log_database_operation("恢复账户数据", Some("ItemTable"), true);
+
+    // 恢复成功后更新当前账户、最后切换时间和切换历史
+    let entry =
+        match account_registry::update_registry(|registry, _| registry.mark_switched(&entry.id)) {
+            Ok(updated) => updated,
+            Err(e) => {
+                log::warn!("⚠️ 更新切换信息失败: {}", e);
+                entry
+            }
+        };
+
+    // 开始统计新账户的使用时长
+    crate::account_activity::start_session(&entry);
+
+    Ok((entry, restore_result))
+}
+
+/// 弹窗询问是否保存未知的当前账户
+async fn confirm_save_unknown_account(app: &AppHandle, email: &str) -> bool {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .message(format!(
+            "当前登录的账户 {} 尚未保存，切换后其登录状态将丢失。\n是否在切换前保存该账户？",
+            email
+        ))
+        .title("保存当前账户")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "保存".to_string(),
+            "不保存".to_string(),
+        ))
+        .show(move |save| {
+            let _ = tx.send(save);
+        });
+    rx.await.unwrap_or(false)
+}
+
+/// 预检时确认当前登录的账户是否需要在切换前保存
+///
+/// 账户未保存过时按 `UnknownAccountPolicy` 处理；需要询问时在关闭 Antigravity 之前弹窗，
+/// 用户选择不保存也不会导致 Antigravity 已被关闭
+async fn check_live_account(ctx: &mut SwitchContext) -> Result<Option<String>, String> {
+    if !ctx.options.backup_current {
+        return Ok(None);
+    }
+    let Some(email) = crate::antigravity_backup::read_current_email()? else {
+        return Ok(None);
+    };
+
+    let known = account_registry::load_registry()?
+        .find_by_email(&email)
+        .is_some();
+    let save = known
+        || match (ctx.options.unknown_account, &ctx.app) {
+            (UnknownAccountPolicy::Skip, _) => false,
+            (UnknownAccountPolicy::Prompt, Some(app)) => {
+                confirm_save_unknown_account(app, &email).await
+            }
+            _ => true,
+        };
+
+    let note = (!known).then(|| {
+        if save {
+            format!("当前账户 {} 未保存过，将在切换前保存", email)
+        } else {
+            format!("当前账户 {} 未保存过，按设置不保存", email)
+        }
+    });
+    ctx.live_email = Some(email);
+    ctx.save_live_account = save;
+    Ok(note)
+}
+
+/// 切换前备份当前登录的账户
+///
+/// 未登录时跳过；账户未保存过时使用预检时确认的结果
+async fn backup_live_account(ctx: &SwitchContext) -> Result<StepOutcome, String> {
+    if !ctx.options.backup_current {
+        return Ok(StepOutcome::Skipped("未启用切换前自动备份".to_string()));
+    }
+
+    let Some(email) = crate::antigravity_backup::read_current_email()? else {
+        return Ok(StepOutcome::Skipped("当前未登录，无需备份".to_string()));
+    };
+
+    let known = account_registry::load_registry()?
+        .find_by_email(&email)
+        .is_some();
+    if !known {
+        // 关闭后登录账户与预检时不同（例如退出时被改写）时不再询问，除非设置为不保存，否则保存以免丢失
+        let save = match &ctx.live_email {
+            Some(live) if live.eq_ignore_ascii_case(&email) => ctx.save_live_account,
+            _ => ctx.options.unknown_account != UnknownAccountPolicy::Skip,
+        };
+        if !save {
+            return Ok(StepOutcome::Skipped(format!(
+                "当前账户 {} 未保存过，已按设置跳过备份",
+                email
+            )));
+        }
+    }
+
+    let (backup_name, is_overwrite) =
+        crate::antigravity_backup::smart_backup_antigravity_account(&email)?;
+    let action = if is_overwrite { "更新" } else { "创建" };
+    Ok(StepOutcome::Done(format!(
+        "已{}当前账户备份: {}",
+        action, backup_name
+    )))
+}
+
+/// 执行单个步骤
+async fn execute_step(ctx: &mut SwitchContext, step: SwitchStep) -> Result<StepOutcome, String> {
+    match step {
+        SwitchStep::Preflight => {
+            ctx.was_running = crate::platform_utils::is_antigravity_process_running();
+
+            if ctx.kind == OperationKind::BackupAndRestart {
+                let email = crate::antigravity_backup::read_current_email()?
+                    .ok_or_else(|| "认证信息中未找到邮箱".to_string())?;
+                let message = format!("当前登录账户: {}", email);
+                ctx.live_email = Some(email);
+                return Ok(StepOutcome::Done(message));
+            }
+
+            let key = ctx.target_key.clone().ok_or("未指定目标账户")?;
+            account_registry::validate_account_key(&key)?;
+            let (entry, backup_file) = account_registry::resolve_account(&key)?;
+            if !backup_file.exists() {
+                return Err(format!("账户备份文件不存在: {}", backup_file.display()));
+            }
+            crate::platform_utils::get_antigravity_db_path().ok_or("未找到Antigravity安装位置")?;
+
+            let mut message = format!("目标账户: {} (ID: {})", entry.email, entry.id);
+            ctx.target = Some((entry, backup_file));
+            if let Some(note) = check_live_account(ctx).await? {
+                message = format!("{}，{}", message, note);
+            }
+            Ok(StepOutcome::Done(message))
+        }
+        SwitchStep::BackupCurrent => match ctx.kind {
+            OperationKind::BackupAndRestart => {
+                let email = ctx.live_email.clone().ok_or("未获取到当前账户")?;
+                let (backup_name, is_overwrite) =
+                    crate::antigravity_backup::smart_backup_antigravity_account(&email)?;
+                let action = if is_overwrite { "更新" } else { "创建" };
+                Ok(StepOutcome::Done(format!(
+                    "已{}备份: {}",
+                    action, backup_name
+                )))
+            }
+            _ => backup_live_account(ctx).await,
+        },
+        SwitchStep::Stop => {
+            if ctx.kind == OperationKind::SwitchInPlace && !ctx.was_running {
+                return Ok(StepOutcome::Skipped(
+                    "Antigravity 未运行，原地切换".to_string(),
+                ));
+            }
+
+            let result = stop_antigravity().await?;
+            // 等待进程真正退出并释放数据库，避免 Antigravity 退出时回写的状态覆盖恢复的数据
+            let waited = crate::antigravity_process::wait_for_antigravity_exit(
+                crate::antigravity_process::DEFAULT_RELEASE_TIMEOUT,
+            )
+            .await?;
+            Ok(StepOutcome::Done(format!(
+                "{}（等待 {} ms）",
+                result,
+                waited.as_millis()
+            )))
+        }
+        SwitchStep::Restore => {
+            let (entry, _) = ctx.target.clone().ok_or("未解析到目标账户")?;
+            let (entry, result) = restore_account(&entry.id).await?;
+            ctx.account = Some(entry);
+
+            // 确认写入完成、数据库未被锁定后再继续
+            crate::antigravity_process::wait_for_database_release(
+                crate::antigravity_process::DEFAULT_RELEASE_TIMEOUT,
+            )
+            .await?;
+            Ok(StepOutcome::Done(result))
+        }
+        SwitchStep::Logout => {
+            let result = crate::antigravity_cleanup::clear_all_antigravity_data().await?;
+            Ok(StepOutcome::Done(result))
+        }
+        SwitchStep::Verify => {
+            let live_email = crate::antigravity_backup::read_current_email()?;
+            match (&ctx.account, live_email) {
+                (Some(account), Some(email)) if account.email.eq_ignore_ascii_case(&email) => {
+                    Ok(StepOutcome::Done(format!("已确认当前登录账户: {}", email)))
+                }
+                (Some(account), live) => Err(format!(
+                    "校验失败：期望登录 {}，实际为 {}",
+                    account.email,
+                    live.unwrap_or_else(|| "未登录".to_string())
+                )),
+                (None, None) => Ok(StepOutcome::Done("已确认处于登出状态".to_string())),
+                (None, Some(email)) => Err(format!("校验失败：仍处于登录状态 ({})", email)),
+            }
+        }
+        SwitchStep::Launch => {
+            if ctx.kind == OperationKind::SwitchInPlace && !ctx.was_running {
+                return Ok(StepOutcome::Skipped(
+                    "Antigravity 原本未运行，无需启动".to_string(),
+                ));
+            }
+
+            crate::antigravity_starter::start_antigravity()
+                .map(StepOutcome::Done)
+                .map_err(|e| format!("启动失败: {}", e))
+        }
+    }
+}
+
+/// 发送步骤进度事件
+fn emit_progress(
+    app: Option<&AppHandle>,
+    ctx: &SwitchContext,
+    step: SwitchStep,
+    status: StepStatus,
+    message: &str,
+    duration_ms: Option<u64>,
+) {
+    log::info!("🔄 [{:?}] {:?}: {}", step, status, message);
+
+    if let Some(app) = app {
+        let payload = SwitchProgress {
+            kind: ctx.kind,
+            target: ctx.target_key.clone(),
+            step,
+            status,
+            message: message.to_string(),
+            duration_ms,
+        };
+        if let Err(e) = app.emit(SWITCH_PROGRESS_EVENT, payload) {
+            log::warn!("⚠️ 发送切换进度事件失败: {}", e);
+        }
+    }
+}
+
+/// 执行单个步骤并上报进度
+async fn run_step(
+    app: Option<&AppHandle>,
+    ctx: &mut SwitchContext,
+    step: SwitchStep,
+) -> StepReport {
+    emit_progress(app, ctx, step, StepStatus::Running, "", None);
+    let step_started = Instant::now();
+    let result = execute_step(ctx, step).await;
+    let duration_ms = step_started.elapsed().as_millis() as u64;
+
+    let (status, message) = match result {
+        Ok(StepOutcome::Done(message)) => (StepStatus::Succeeded, message),
+        Ok(StepOutcome::Skipped(message)) => (StepStatus::Skipped, message),
+        Err(e) => (StepStatus::Failed, e),
+    };
+
+    emit_progress(app, ctx, step, status, &message, Some(duration_ms));
+    StepReport {
+        step,
+        status,
+        message,
+        duration_ms,
+    }
+}
+
+/// 按轮换方向切换到下一个 / 上一个 / 上一次使用的账户
+pub async fn switch_in_rotation(
+    app: Option<&AppHandle>,
+    direction: account_registry::RotationDirection,
+) -> Result<SwitchReport, String> {
+    if is_operation_running() {
+        return Err("已有账户切换操作正在进行，请稍后再试".to_string());
+    }
+
+    let target = account_registry::load_registry()?.rotation_target(direction)?;
+    log::info!("🔁 轮换切换 ({:?}) -> {}", direction, target.email);
+    run_operation(app, OperationKind::Switch, Some(target.id), None).await
+}
+
+/// 按步骤执行切换类操作
+///
+/// `backup_current` 为空时使用保存的切换选项。
+/// 只有在已有操作正在执行时返回 `Err`；步骤失败或被取消时返回 `success = false` 的报告
+pub async fn run_operation(
+    app: Option<&AppHandle>,
+    kind: OperationKind,
+    target: Option<String>,
+    backup_current: Option<bool>,
+) -> Result<SwitchReport, String> {
+    let guard = OperationGuard::acquire()?;
+    let started = Instant::now();
+    crate::system_tray::SystemTrayManager::refresh_status();
+
+    let mut options = crate::settings::get().switch_options;
+    if let Some(backup_current) = backup_current {
+        options.backup_current = backup_current;
+    }
+
+    let mut ctx = SwitchContext {
+        app: app.cloned(),
+        kind,
+        options,
+        target_key: target,
+        target: None,
+        live_email: None,
+        save_live_account: true,
+        was_running: false,
+        account: None,
+    };
+
+    let plan = kind.plan();
+    let mut steps: Vec<StepReport> = Vec::new();
+    let mut success = true;
+    let mut cancelled = false;
+
+    // 是否已经关闭了原本在运行的 Antigravity
+    let mut stopped = false;
+
+    for (index, &step) in plan.iter().enumerate() {
+        // 在步骤之间响应取消请求
+        if CANCEL_REQUESTED.load(SeqCst) {
+            cancelled = true;
+            success = false;
+            // 已关闭 Antigravity 时仍执行启动步骤，避免取消后 Antigravity 一直处于关闭状态
+            let relaunch = stopped && plan[index..].contains(&SwitchStep::Launch);
+            for &remaining in &plan[index..] {
+                if relaunch && remaining == SwitchStep::Launch {
+                    continue;
+                }
+                emit_progress(app, &ctx, remaining, StepStatus::Cancelled, "已取消", None);
+                steps.push(StepReport {
+                    step: remaining,
+                    status: StepStatus::Cancelled,
+                    message: "已取消".to_string(),
+                    duration_ms: 0,
+                });
+            }
+            if relaunch {
+                steps.push(run_step(app, &mut ctx, SwitchStep::Launch).await);
+            }
+            break;
+        }
+
+        let report = run_step(app, &mut ctx, step).await;
+        let status = report.status;
+        steps.push(report);
+
+        if step == SwitchStep::Stop && status == StepStatus::Succeeded && ctx.was_running {
+            stopped = true;
+        }
+        if status == StepStatus::Failed && step.is_critical() {
+            success = false;
+            break;
+        }
+    }
+
+    let summary = steps
+        .iter()
+        .filter(|s| matches!(s.status, StepStatus::Succeeded | StepStatus::Failed))
+        .map(|s| s.message.as_str())
+        .collect::<Vec<_>>()
+        .join(" -> ");
+    let summary = if cancelled {
+        format!("操作已取消: {}", summary)
+    } else {
+        summary
+    };
+
+    let report = SwitchReport {
+        kind,
+        target: ctx.target_key.clone(),
+        account: ctx.account.clone(),
+        success,
+        cancelled,
+        steps,
+        summary,
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+
+    log::info!(
+        "🎉 操作结束 ({:?}): 成功={} 耗时={}ms",
+        kind,
+        report.success,
+        report.duration_ms
+    );
+    if let Some(app) = app {
+        if let Err(e) = app.emit(SWITCH_FINISHED_EVENT, report.clone()) {
+            log::warn!("⚠️ 发送切换完成事件失败: {}", e);
+        }
+
+        // 用户主动取消的操作不再通知
+        if !cancelled {
+            let event = match kind {
+                OperationKind::Switch | OperationKind::SwitchInPlace => NotificationEvent::Switch,
+                OperationKind::BackupAndRestart => NotificationEvent::Logout,
+            };
+            let result = if success {
+                Ok(report.summary.clone())
+            } else {
+                Err(report.summary.clone())
+            };
+            crate::notifications::notify_result(app, event, &result);
+        }
+    }
+
+    // 释放操作锁后再刷新托盘状态，避免仍显示为“切换中”
+    drop(guard);
+    crate::system_tray::SystemTrayManager::refresh_status();
+
+    Ok(report)
+}
@@ -1,11 +1,11 @@
 // Antigravity 用户数据备份模块
 // 负责将 Antigravity 应用数据备份到 JSON 文件
 
-use rusqlite::{Connection, OptionalExtension};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
 use serde_json::Value;
 use std::fs;
-use std::path::PathBuf;
 
+use crate::account_registry;
 use crate::constants::database;
 use crate::platform_utils;
 
@@ -16,6 +16,8 @@ use crate::platform_utils;
 /// 2. 保存完整的 __$__targetStorageMarker 对象（作为恢复时的参考）
 /// 3. 保存 __$__isNewStorageMarker 状态标记
 ///
+/// 备份文件名由账户注册表分配（经过清洗），同一邮箱（忽略大小写）始终写入同一个文件
+///
 /// # 参数
 /// - `email`: 用户邮箱
 ///
@@ -25,15 +27,7 @@ use crate::platform_utils;
 pub fn smart_backup_antigravity_account(email: &str) -> Result<(String, bool), String> {
     log::info!("🔧 执行智能备份（完整 Marker 模式），邮箱: {}", email);
 
-    let config_dir = dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".antigravity-agent")
-        .join("antigravity-accounts");
-    fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
-
-    // 简单的覆盖逻辑：每个邮箱只保留一个备份
-    let backup_name = email.to_string();
-    let is_overwrite = config_dir.join(format!("{}.json", backup_name)).exists();
+    account_registry::validate_account_key(email)?;
 
     let app_data = platform_utils::get_antigravity_db_path().ok_or("未找到数据库路径")?;
 
@@ -86,22 +80,120 @@ pub fn smart_backup_antigravity_account(email: &str) -> Result<(String, bool), S
         }
     }
 
-    // 3. 添加元信息
-    data_map.insert(
-        "account_email".to_string(),
-        Value::String(email.to_string()),
-    );
-    data_map.insert(
-        "backup_time".to_string(),
-        Value::String(chrono::Local::now().to_rfc3339()),
-    );
-
-    // 4. 写入备份文件
-    let backup_file = config_dir.join(format!("{}.json", backup_name));
-    let file_content = serde_json::to_string_pretty(&data_map).map_err(|e| e.to_string())?;
-    fs::write(&backup_file, file_content).map_err(|e| e.to_string())?;
-
-    let action = if is_overwrite { "覆盖" } else { "创建" };
-    println!("✅ 备份成功 ({}): {}", action, backup_file.display());
-    Ok((backup_name, is_overwrite))
+    // 3. 在注册表中登记账户并写入备份文件（每个账户只保留一个备份）
+    account_registry::update_registry(|registry, accounts_dir| {
+        let entry = registry.ensure_account(email)?;
+        let backup_file = account_registry::backup_file_path(accounts_dir, &entry)?;
+        let is_overwrite = backup_file.exists();
+
+        // 添加元信息
+        data_map.insert(
+            account_registry::ACCOUNT_ID_FIELD.to_string(),
+            Value::String(entry.id.clone()),
+        );
+        data_map.insert(
+            account_registry::ACCOUNT_EMAIL_FIELD.to_string(),
+            Value::String(email.to_string()),
+        );
+        data_map.insert(
+            "backup_time".to_string(),
+            Value::String(chrono::Local::now().to_rfc3339()),
+        );
+
+        let file_content = serde_json::to_string_pretty(&data_map).map_err(|e| e.to_string())?;
+        fs::write(&backup_file, file_content).map_err(|e| e.to_string())?;
+
+        let action = if is_overwrite { "覆盖" } else { "创建" };
+        println!("✅ 备份成功 ({}): {}", action, backup_file.display());
+        Ok((entry.file_name, is_overwrite))
+    })
+}
+
+/// 比较当前登录账户的实时数据与已保存的备份，返回发生变化的字段
+///
+/// 只比较 `FRESHNESS_KEYS` 中的字段；账户未保存过时返回 `Ok(None)`，
+/// 备份文件缺失或无法解析时认为所有字段都已变化
+pub fn stale_backup_keys(
+    email: &str,
+) -> Result<Option<(account_registry::AccountEntry, Vec<String>)>, String> {
+    let registry = account_registry::load_registry()?;
+    let Some(entry) = registry.find_by_email(email).cloned() else {
+        return Ok(None);
+    };
+
+    let accounts_dir = crate::config_manager::ConfigManager::new()?.accounts_dir()?;
+    let backup_file = account_registry::backup_file_path(&accounts_dir, &entry)?;
+    let backup: Value = fs::read_to_string(&backup_file)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or(Value::Null);
+
+    let app_data = platform_utils::get_antigravity_db_path().ok_or("未找到数据库路径")?;
+    let conn = Connection::open_with_flags(&app_data, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("连接数据库失败: {}", e))?;
+
+    let mut changed = Vec::new();
+    for key in database::FRESHNESS_KEYS {
+        let live: Option<String> = conn
+            .query_row("SELECT value FROM ItemTable WHERE key = ?", [key], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(|e| format!("查询字段 {} 失败: {}", key, e))?;
+
+        // 实时数据中不存在的字段（例如已登出）不视为备份过期
+        if let Some(live) = live {
+            if backup.get(*key).and_then(|v| v.as_str()) != Some(live.as_str()) {
+                changed.push(key.to_string());
+            }
+        }
+    }
+
+    Ok(Some((entry, changed)))
+}
+
+/// 读取 Antigravity 当前的认证信息原始值
+///
+/// 以只读方式打开数据库，未登录（认证信息不存在）时返回 `Ok(None)`
+pub fn read_auth_status() -> Result<Option<String>, String> {
+    let app_data = platform_utils::get_antigravity_db_path().ok_or("未找到数据库路径")?;
+
+    if !app_data.exists() {
+        return Err(format!("数据库文件不存在: {}", app_data.display()));
+    }
+
+    let conn = Connection::open_with_flags(&app_data, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("连接数据库失败: {}", e))?;
+
+    let auth_str: Option<String> = conn
+        .query_row(
+            "SELECT value FROM ItemTable WHERE key = ?",
+            [database::AUTH_STATUS],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("查询认证信息失败: {}", e))?;
+
+    Ok(auth_str)
+}
+
+/// 从认证信息原始值中解析邮箱
+pub fn parse_auth_email(auth_str: &str) -> Result<Option<String>, String> {
+    let auth_data: Value =
+        serde_json::from_str(auth_str).map_err(|e| format!("解析认证信息失败: {}", e))?;
+
+    Ok(auth_data
+        .get("email")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string()))
+}
+
+/// 读取 Antigravity 当前登录账户的邮箱
+///
+/// 未登录（认证信息不存在）时返回 `Ok(None)`
+pub fn read_current_email() -> Result<Option<String>, String> {
+    match read_auth_status()? {
+        Some(auth_str) => parse_auth_email(&auth_str),
+        None => Ok(None),
+    }
 }
@@ -130,5 +130,8 @@ pub async fn clear_all_antigravity_data() -> Result<String, String> {
         println!("  ℹ️ 备份数据库不存在，跳过");
     }
 
+    // 登出后结束当前账户的使用会话
+    crate::account_activity::end_session(crate::account_activity::SessionEndReason::Logout);
+
     Ok(format!("✅ 登出成功: {}", msg))
 }
@@ -0,0 +1,392 @@
+// Antigravity 进程状态模块
+// 负责查找、关闭 Antigravity 进程，以及等待进程退出、状态数据库释放等与进程生命周期相关的操作
+
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, Process, ProcessStatus, System};
+
+use crate::platform_utils;
+
+/// 等待进程退出并释放数据库的默认超时时间
+pub const DEFAULT_RELEASE_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// 发送正常退出信号后，等待进程自行退出的时间，超时后强制结束
+const GRACEFUL_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 强制结束后等待进程消失的时间
+const FORCED_EXIT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 进程监控最近一次观察到的运行状态
+///
+/// 托盘状态、使用时长汇总等只需要近似状态的地方读取该值，避免重复扫描全部进程
+static LAST_KNOWN_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 记录进程监控观察到的运行状态，返回状态是否发生了变化
+pub fn record_running_state(running: bool) -> bool {
+    LAST_KNOWN_RUNNING.swap(running, SeqCst) != running
+}
+
+/// 进程监控最近一次观察到的运行状态
+pub fn last_known_running() -> bool {
+    LAST_KNOWN_RUNNING.load(SeqCst)
+}
+
+/// 运行中的 Antigravity 进程信息
+#[derive(Debug, Clone, Serialize)]
+pub struct AntigravityProcess {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    /// 可执行文件路径（权限不足时可能无法读取）
+    pub exe: Option<String>,
+    /// 是否为 Electron 辅助进程（渲染、GPU、扩展宿主等）
+    pub is_helper: bool,
+    /// 已运行时长（秒）
+    pub uptime_secs: u64,
+}
+
+/// 进程关闭结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TerminationReport {
+    /// 收到正常退出信号后自行退出的进程
+    pub graceful: Vec<u32>,
+    /// 超时后被强制结束的进程
+    pub forced: Vec<u32>,
+    /// 强制结束后仍未退出的进程
+    pub remaining: Vec<u32>,
+}
+
+impl TerminationReport {
+    /// 生成用于界面展示的结果描述
+    pub fn summary(&self) -> String {
+        if self.graceful.is_empty() && self.forced.is_empty() && self.remaining.is_empty() {
+            return "Antigravity 进程未运行".to_string();
+        }
+
+        let mut parts = Vec::new();
+        if !self.graceful.is_empty() {
+            parts.push(format!("正常退出 PID {:?}", self.graceful));
+        }
+        if !self.forced.is_empty() {
+            parts.push(format!("强制结束 PID {:?}", self.forced));
+        }
+        if !self.remaining.is_empty() {
+            parts.push(format!("仍在运行 PID {:?}", self.remaining));
+        }
+        format!("已关闭 Antigravity 进程: {}", parts.join("，"))
+    }
+}
+
+/// 判断可执行文件名是否属于 Antigravity（主进程或 Electron 辅助进程）
+///
+/// 按完整文件名匹配，不会匹配到 `antigravity-agent` 或命令行中包含该单词的其他进程
+fn is_antigravity_name(name: &str) -> bool {
+    let stem = name.strip_suffix(".exe").unwrap_or(name);
+    stem.eq_ignore_ascii_case("antigravity") || stem.starts_with("Antigravity Helper")
+}
+
+/// 判断进程是否为 Antigravity 进程
+///
+/// 优先使用可执行文件路径的文件名，无法读取时回退到进程名
+fn is_antigravity_process(process: &Process) -> bool {
+    if matches!(
+        process.status(),
+        ProcessStatus::Zombie | ProcessStatus::Dead
+    ) {
+        return false;
+    }
+
+    match process.exe().and_then(|exe| exe.file_name()) {
+        Some(file_name) => is_antigravity_name(&file_name.to_string_lossy()),
+        None => is_antigravity_name(process.name()),
+    }
+}
+
+/// 获取需要排除的进程：Agent 自身及其所有父进程
+///
+/// 即使 Agent 是从 Antigravity 的集成终端启动的，也不会关闭自己所在的进程链
+fn own_process_chain(system: &System) -> HashSet<Pid> {
+    let mut chain = HashSet::new();
+    let mut current = sysinfo::get_current_pid().ok();
+
+    while let Some(pid) = current {
+        if !chain.insert(pid) {
+            break;
+        }
+        current = system.process(pid).and_then(|p| p.parent());
+    }
+
+    chain
+}
+
+/// 在进程快照中查找所有 Antigravity 进程（排除 Agent 自身的进程链）
+fn antigravity_pids(system: &System) -> Vec<Pid> {
+    let excluded = own_process_chain(system);
+    let own_exe = std::env::current_exe().ok();
+
+    let mut pids: Vec<Pid> = system
+        .processes()
+        .iter()
+        .filter(|(pid, process)| {
+            !excluded.contains(pid)
+                && own_exe
+                    .as_deref()
+                    .is_none_or(|own| process.exe() != Some(own))
+                && is_antigravity_process(process)
+        })
+        .map(|(pid, _)| *pid)
+        .collect();
+    pids.sort();
+    pids
+}
+
+/// 判断进程是否为 Electron 辅助进程
+///
+/// 父进程同为 Antigravity，或命令行带有 `--type=` 参数的进程视为辅助进程
+fn is_helper_process(process: &Process, antigravity: &HashSet<Pid>) -> bool {
+    process
+        .parent()
+        .is_some_and(|parent| antigravity.contains(&parent))
+        || process.cmd().iter().any(|arg| arg.starts_with("--type="))
+}
+
+/// 查找所有运行中的 Antigravity 进程（包括 Electron 辅助进程），按 PID 排序
+pub fn find_antigravity_processes() -> Vec<AntigravityProcess> {
+    let mut system = System::new();
+    system.refresh_processes();
+
+    let pids = antigravity_pids(&system);
+    let pid_set: HashSet<Pid> = pids.iter().copied().collect();
+
+    pids.iter()
+        .filter_map(|pid| system.process(*pid).map(|process| (pid, process)))
+        .map(|(pid, process)| AntigravityProcess {
+            pid: pid.as_u32(),
+            parent_pid: process.parent().map(|parent| parent.as_u32()),
+            name: process.name().to_string(),
+            exe: process.exe().map(|exe| exe.to_string_lossy().to_string()),
+            is_helper: is_helper_process(process, &pid_set),
+            uptime_secs: process.run_time(),
+        })
+        .collect()
+}
+
+/// 在快照中筛选出仍然存活的进程
+fn alive_pids(system: &System, pids: &[Pid]) -> Vec<Pid> {
+    pids.iter()
+        .copied()
+        .filter(|pid| {
+            system.process(*pid).is_some_and(|process| {
+                !matches!(
+                    process.status(),
+                    ProcessStatus::Zombie | ProcessStatus::Dead
+                )
+            })
+        })
+        .collect()
+}
+
+/// 向进程发送正常退出请求
+///
+/// Unix 下发送 SIGTERM；Windows 下使用不带 /F 的 taskkill 发送关闭消息
+fn request_graceful_exit(system: &System, pid: Pid) -> bool {
+    if cfg!(windows) {
+        std::process::Command::new("taskkill")
+            .args(["/PID", &pid.as_u32().to_string()])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    } else {
+        system
+            .process(pid)
+            .and_then(|process| process.kill_with(sysinfo::Signal::Term))
+            .unwrap_or(false)
+    }
+}
+
+/// 轮询等待指定进程全部退出，返回超时后仍存活的进程
+async fn wait_for_pids_exit(system: &mut System, pids: &[Pid], timeout: Duration) -> Vec<Pid> {
+    let started = Instant::now();
+    loop {
+        system.refresh_processes();
+        let alive = alive_pids(system, pids);
+        if alive.is_empty() || started.elapsed() >= timeout {
+            return alive;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn to_u32(pids: &[Pid]) -> Vec<u32> {
+    pids.iter().map(|pid| pid.as_u32()).collect()
+}
+
+/// 关闭所有 Antigravity 进程
+///
+/// 先向主进程发送正常退出请求（辅助进程会随主进程退出），超时后强制结束剩余进程。
+/// 只有在强制结束后仍有进程存活时才返回错误
+pub async fn terminate_antigravity() -> Result<TerminationReport, String> {
+    let mut system = System::new();
+    system.refresh_processes();
+
+    let targets = antigravity_pids(&system);
+    if targets.is_empty() {
+        println!("ℹ️ 未发现运行中的 Antigravity 进程");
+        return Ok(TerminationReport::default());
+    }
+    println!("🔍 发现 Antigravity 进程: {:?}", to_u32(&targets));
+
+    // 只向主进程发送退出请求，辅助进程由主进程负责关闭
+    let target_set: HashSet<Pid> = targets.iter().copied().collect();
+    for pid in &targets {
+        let Some(process) = system.process(*pid) else {
+            continue;
+        };
+        if !is_helper_process(process, &target_set) {
+            // 记住运行实例的可执行文件路径，便于之后重新启动
+            if let Some(exe) = process.exe() {
+                if let Err(e) = platform_utils::persist_antigravity_path(exe) {
+                    log::warn!("⚠️ 保存 Antigravity 路径失败: {}", e);
+                }
+            }
+
+            let sent = request_graceful_exit(&system, *pid);
+            println!(
+                "📨 请求进程 {} 正常退出: {}",
+                pid,
+                if sent { "已发送" } else { "失败" }
+            );
+        }
+    }
+
+    let pending = wait_for_pids_exit(&mut system, &targets, GRACEFUL_EXIT_TIMEOUT).await;
+    let graceful: Vec<Pid> = targets
+        .iter()
+        .copied()
+        .filter(|pid| !pending.contains(pid))
+        .collect();
+
+    if !pending.is_empty() {
+        log::warn!(
+            "⚠️ 进程未在规定时间内退出，强制结束: {:?}",
+            to_u32(&pending)
+        );
+        for pid in &pending {
+            if let Some(process) = system.process(*pid) {
+                process.kill();
+            }
+        }
+    }
+
+    let remaining = if pending.is_empty() {
+        Vec::new()
+    } else {
+        wait_for_pids_exit(&mut system, &pending, FORCED_EXIT_TIMEOUT).await
+    };
+    let forced: Vec<Pid> = pending
+        .iter()
+        .copied()
+        .filter(|pid| !remaining.contains(pid))
+        .collect();
+
+    let report = TerminationReport {
+        graceful: to_u32(&graceful),
+        forced: to_u32(&forced),
+        remaining: to_u32(&remaining),
+    };
+
+    if report.remaining.is_empty() {
+        log::info!("✅ {}", report.summary());
+        Ok(report)
+    } else {
+        Err(format!(
+            "无法关闭部分 Antigravity 进程: {}",
+            report.summary()
+        ))
+    }
+}
+
+/// 尝试获取数据库的排他写锁，成功后立即释放
+///
+/// 数据库文件不存在时视为已释放
+fn try_exclusive_lock(db_path: &Path) -> Result<(), String> {
+    if !db_path.exists() {
+        return Ok(());
+    }
+
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_WRITE)
+        .map_err(|e| format!("打开数据库失败: {}", e))?;
+    conn.busy_timeout(Duration::ZERO)
+        .map_err(|e| format!("设置数据库超时失败: {}", e))?;
+    conn.execute_batch("BEGIN EXCLUSIVE; COMMIT;")
+        .map_err(|e| format!("数据库仍被占用: {}", e))
+}
+
+/// 轮询直到条件满足或超时
+///
+/// `check` 返回 `Ok(())` 表示条件满足，返回 `Err(reason)` 表示仍需等待，
+/// 超时后以最后一次的原因作为错误信息
+async fn poll_until(
+    what: &str,
+    timeout: Duration,
+    mut check: impl FnMut() -> Result<(), String>,
+) -> Result<Duration, String> {
+    let started = Instant::now();
+
+    loop {
+        let reason = match check() {
+            Ok(()) => {
+                let waited = started.elapsed();
+                log::info!("✅ {}（等待 {} ms）", what, waited.as_millis());
+                return Ok(waited);
+            }
+            Err(reason) => reason,
+        };
+
+        if started.elapsed() >= timeout {
+            return Err(format!(
+                "等待{}超时（{} 秒）: {}",
+                what,
+                timeout.as_secs(),
+                reason
+            ));
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// 等待状态数据库可以获取排他锁（例如恢复数据后确认写入已完成）
+pub async fn wait_for_database_release(timeout: Duration) -> Result<Duration, String> {
+    let db_path = platform_utils::get_antigravity_db_path().ok_or("未找到数据库路径")?;
+    poll_until("状态数据库释放", timeout, || {
+        try_exclusive_lock(&db_path)
+    })
+    .await
+}
+
+/// 等待所有 Antigravity 进程退出，并且状态数据库可以获取排他锁
+pub async fn wait_for_antigravity_exit(timeout: Duration) -> Result<Duration, String> {
+    let db_path = platform_utils::get_antigravity_db_path();
+    poll_until(
+        "Antigravity 进程退出并释放数据库",
+        timeout,
+        || {
+            if platform_utils::is_antigravity_process_running() {
+                return Err("Antigravity 进程仍在运行".to_string());
+            }
+            match &db_path {
+                Some(path) => try_exclusive_lock(path),
+                None => Ok(()),
+            }
+        },
+    )
+    .await
+}
@@ -79,7 +79,13 @@ fn start_antigravity_windows() -> Result<String, String> {
 /// 在 macOS 平台启动 Antigravity
 fn start_antigravity_macos() -> Result<String, String> {
     let mut errors = Vec::new();
-    let antigravity_paths = get_antigravity_macos_paths();
+    let mut antigravity_paths = get_antigravity_macos_paths();
+
+    // 优先使用从运行实例中记录的路径
+    if let Some(persisted) = crate::platform_utils::load_persisted_antigravity_path() {
+        antigravity_paths.retain(|p| p != &persisted);
+        antigravity_paths.insert(0, persisted);
+    }
 
     // 尝试所有推测的路径
     for path in &antigravity_paths {
@@ -115,7 +121,13 @@ fn start_antigravity_macos() -> Result<String, String> {
 /// 在 Linux 平台启动 Antigravity
 fn start_antigravity_linux() -> Result<String, String> {
     let mut errors = Vec::new();
-    let antigravity_paths = get_antigravity_linux_paths();
+    let mut antigravity_paths = get_antigravity_linux_paths();
+
+    // 优先使用从运行实例中记录的路径
+    if let Some(persisted) = crate::platform_utils::load_persisted_antigravity_path() {
+        antigravity_paths.retain(|p| p != &persisted);
+        antigravity_paths.insert(0, persisted);
+    }
 
     // 尝试所有推测的路径
     for path in &antigravity_paths {
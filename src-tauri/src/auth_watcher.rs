@@ -0,0 +1,182 @@
+// Antigravity 登录状态监听模块
+// 监听 globalStorage 下的 state.vscdb 及其 -wal / -journal 文件，
+// 防抖后重新读取 antigravityAuthStatus，并通过 Tauri 事件通知登录、登出和令牌刷新
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::antigravity_backup;
+use crate::platform_utils;
+
+/// 登录状态变化事件
+pub const AUTH_CHANGED_EVENT: &str = "antigravity-auth://changed";
+
+/// 防抖时间：在此时间内没有新的文件变化才重新读取数据库
+const DEBOUNCE: Duration = Duration::from_millis(1000);
+
+/// 需要监听的数据库文件
+const WATCHED_FILES: [&str; 3] = ["state.vscdb", "state.vscdb-wal", "state.vscdb-journal"];
+
+/// 登录状态变化
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthEvent {
+    /// 登录了某个账户（包括从其他账户直接切换过来）
+    LoggedIn {
+        email: String,
+        previous_email: Option<String>,
+    },
+    /// 已登出
+    LoggedOut { previous_email: String },
+    /// 同一账户的认证信息（令牌）发生了变化
+    TokensRefreshed { email: String },
+}
+
+/// 某一时刻的登录状态
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct AuthSnapshot {
+    email: Option<String>,
+    auth_status: Option<String>,
+}
+
+impl AuthSnapshot {
+    /// 读取数据库中当前的登录状态
+    fn read() -> Result<Self, String> {
+        let auth_status = antigravity_backup::read_auth_status()?;
+        let email = match &auth_status {
+            Some(raw) => antigravity_backup::parse_auth_email(raw)?,
+            None => None,
+        };
+        Ok(Self { email, auth_status })
+    }
+
+    /// 与上一次的状态比较，得出变化事件
+    fn diff(&self, previous: &AuthSnapshot) -> Option<AuthEvent> {
+        match (&previous.email, &self.email) {
+            (None, Some(email)) => Some(AuthEvent::LoggedIn {
+                email: email.clone(),
+                previous_email: None,
+            }),
+            (Some(old), Some(email)) if !old.eq_ignore_ascii_case(email) => {
+                Some(AuthEvent::LoggedIn {
+                    email: email.clone(),
+                    previous_email: Some(old.clone()),
+                })
+            }
+            (Some(old), None) => Some(AuthEvent::LoggedOut {
+                previous_email: old.clone(),
+            }),
+            (Some(_), Some(email)) if previous.auth_status != self.auth_status => {
+                Some(AuthEvent::TokensRefreshed {
+                    email: email.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 判断文件事件是否与状态数据库有关
+///
+/// 只关心创建、修改和删除；读取数据库产生的访问事件会被忽略，避免自身读取触发循环
+fn is_database_event(event: &notify::Event) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) && event.paths.iter().any(|path| {
+        path.file_name()
+            .is_some_and(|name| WATCHED_FILES.iter().any(|f| name == *f))
+    })
+}
+
+/// 监听循环，直到监听器被关闭
+fn watch_loop(app: &AppHandle, data_dir: &Path) -> Result<(), String> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(tx).map_err(|e| format!("创建文件监听器失败: {}", e))?;
+    // 监听整个目录而不是单个文件，数据库文件被替换或 -wal 文件新建时也能收到通知
+    watcher
+        .watch(data_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("监听目录 {} 失败: {}", data_dir.display(), e))?;
+
+    println!("👀 开始监听登录状态: {}", data_dir.display());
+    let mut last = AuthSnapshot::read().unwrap_or_default();
+
+    // 启动时检查一次当前账户的备份是否过期
+    if let Some(email) = &last.email {
+        crate::backup_freshness::check_live_account(app, email);
+    }
+
+    while let Ok(result) = rx.recv() {
+        match result {
+            Ok(event) if is_database_event(&event) => {}
+            Ok(_) => continue,
+            Err(e) => {
+                log::warn!("⚠️ 文件监听出错: {}", e);
+                continue;
+            }
+        }
+
+        // 防抖：等待文件变化平静下来
+        loop {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+
+        let current = match AuthSnapshot::read() {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                // 数据库可能正被写入或暂时不存在，等待下一次变化
+                log::warn!("⚠️ 读取登录状态失败: {}", e);
+                continue;
+            }
+        };
+
+        if let Some(event) = current.diff(&last) {
+            log::info!("🔔 登录状态变化: {:?}", event);
+            if let Err(e) = app.emit(AUTH_CHANGED_EVENT, &event) {
+                log::warn!("⚠️ 发送登录状态事件失败: {}", e);
+            }
+
+            // 更新托盘菜单中当前账户的勾选状态以及提示文字、图标角标
+            crate::system_tray::SystemTrayManager::refresh_menu();
+            crate::system_tray::SystemTrayManager::refresh_status();
+        }
+
+        // 令牌或设置变化后检查已保存的备份是否过期
+        if let Some(email) = &current.email {
+            crate::backup_freshness::check_live_account(app, email);
+        }
+        last = current;
+    }
+
+    Ok(())
+}
+
+/// 启动登录状态监听（在后台线程中运行）
+pub fn start_auth_watcher(app: AppHandle) {
+    let Some(data_dir) = platform_utils::get_antigravity_data_dir() else {
+        log::warn!("⚠️ 未找到 Antigravity 数据目录，跳过登录状态监听");
+        return;
+    };
+    if !data_dir.exists() {
+        log::warn!(
+            "⚠️ Antigravity 数据目录不存在，跳过登录状态监听: {}",
+            data_dir.display()
+        );
+        return;
+    }
+
+    std::thread::spawn(move || {
+        if let Err(e) = watch_loop(&app, &data_dir) {
+            log::error!("❌ 登录状态监听已停止: {}", e);
+        }
+    });
+}
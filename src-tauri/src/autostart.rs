@@ -0,0 +1,97 @@
+// 开机自启模块
+// 通过 tauri-plugin-autostart 在系统中注册登录自启项（Linux 为 XDG autostart 的 .desktop 文件，
+// macOS 为 LaunchAgent，Windows 为注册表 Run 项），并决定开机自启时是否直接隐藏到托盘
+
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_autostart::ManagerExt;
+
+use crate::config_manager::{ConfigManager, DataDirSource};
+use crate::constants::paths;
+
+/// 自启项的启动参数：标记本次为开机自启，并保留当前使用的数据目录
+fn launch_args() -> Vec<String> {
+    let mut args = vec![paths::AUTOSTART_ARG.to_string()];
+
+    let Ok(config_manager) = ConfigManager::new() else {
+        return args;
+    };
+    match config_manager.data_dir_source() {
+        DataDirSource::CommandLine | DataDirSource::Environment => {
+            args.push(paths::DATA_DIR_ARG.to_string());
+            args.push(config_manager.config_dir().to_string_lossy().into_owned());
+        }
+        DataDirSource::Portable => args.push(paths::PORTABLE_ARG.to_string()),
+        DataDirSource::Default => {}
+    }
+    args
+}
+
+/// 创建开机自启插件（macOS 默认使用 LaunchAgent）
+pub fn plugin<R: Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri_plugin_autostart::Builder::new()
+        .args(launch_args())
+        .build()
+}
+
+/// 本次启动是否由开机自启触发
+pub fn launched_at_login() -> bool {
+    std::env::args().any(|arg| arg == paths::AUTOSTART_ARG)
+}
+
+/// 查询系统中是否已注册开机自启
+pub fn is_enabled(app: &AppHandle) -> Result<bool, String> {
+    app.autolaunch()
+        .is_enabled()
+        .map_err(|e| format!("查询开机自启状态失败: {}", e))
+}
+
+/// 在系统中注册或移除开机自启项，并保存到设置
+pub fn set_enabled(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    register(app, enabled)?;
+    crate::settings::update(|s| s.launch_at_login = enabled)?;
+    log::info!("🚀 开机自启已{}", if enabled { "启用" } else { "禁用" });
+    Ok(())
+}
+
+/// 只修改系统中的自启项，不修改设置
+pub fn register(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let autolaunch = app.autolaunch();
+    let result = if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    result.map_err(|e| {
+        format!(
+            "{}开机自启失败: {}",
+            if enabled { "启用" } else { "禁用" },
+            e
+        )
+    })
+}
+
+/// 启动时让系统中的自启项与设置保持一致
+///
+/// 开启时重新注册一次，使自启项指向当前的可执行文件和数据目录（应用升级或移动后仍然有效）
+pub fn sync_with_settings(app: &AppHandle) {
+    let enabled = crate::settings::get().launch_at_login;
+    let registered = is_enabled(app).unwrap_or(false);
+    if !enabled && !registered {
+        return;
+    }
+
+    if let Err(e) = register(app, enabled) {
+        log::warn!("⚠️ 同步开机自启状态失败: {}", e);
+    }
+}
+
+/// 本次启动是否应保持窗口隐藏：开机自启、设置了隐藏启动且系统托盘可用
+pub fn should_start_hidden() -> bool {
+    if !launched_at_login() || !crate::settings::get().start_hidden {
+        return false;
+    }
+
+    crate::system_tray::SystemTrayManager::get_global()
+        .and_then(|manager| manager.lock().ok().map(|manager| manager.is_enabled()))
+        .unwrap_or(false)
+}
@@ -0,0 +1,129 @@
+// 账户备份保鲜模块
+// 当前登录的账户已保存过、但 Antigravity 刷新了令牌或修改了设置时，
+// 按设置自动或在确认后重新备份，避免之后切换回来时恢复已失效的凭据
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::antigravity_backup;
+
+/// 备份已刷新事件
+pub const BACKUP_REFRESHED_EVENT: &str = "account-backup://refreshed";
+
+/// 是否已有刷新确认弹窗，避免令牌频繁变化时重复弹窗
+static PROMPT_OPEN: AtomicBool = AtomicBool::new(false);
+
+/// 备份过期时的处理方式（保存在设置中）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupRefreshPolicy {
+    /// 自动重新备份
+    #[default]
+    Auto,
+    /// 弹窗确认后重新备份
+    Prompt,
+    /// 不处理
+    Off,
+}
+
+/// 备份已刷新事件载荷
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupRefreshed {
+    pub account_id: String,
+    pub email: String,
+    pub file_name: String,
+    pub changed_keys: Vec<String>,
+}
+
+/// 通过常规备份流程重新备份账户，并发送事件
+///
+/// 备份期间持有操作锁；确认弹窗期间可能已切换账户，只有当前登录的仍是该账户时才备份
+fn refresh_backup(app: &AppHandle, email: &str, changed_keys: Vec<String>) {
+    let result = crate::account_switcher::run_exclusive(|| {
+        let live_email = antigravity_backup::read_current_email()?;
+        if !live_email.is_some_and(|live| live.eq_ignore_ascii_case(email)) {
+            return Err(format!("当前登录的账户已不是 {}", email));
+        }
+        antigravity_backup::smart_backup_antigravity_account(email)
+    });
+
+    match result {
+        Ok((file_name, _)) => {
+            log::info!("🔄 已刷新账户备份: {} ({:?})", email, changed_keys);
+            let payload = BackupRefreshed {
+                account_id: crate::account_registry::load_registry()
+                    .ok()
+                    .and_then(|r| r.find_by_email(email).map(|e| e.id.clone()))
+                    .unwrap_or_default(),
+                email: email.to_string(),
+                file_name,
+                changed_keys,
+            };
+            if let Err(e) = app.emit(BACKUP_REFRESHED_EVENT, payload) {
+                log::warn!("⚠️ 发送备份刷新事件失败: {}", e);
+            }
+        }
+        Err(e) => log::warn!("⚠️ 刷新账户备份失败 ({}): {}", email, e),
+    }
+}
+
+/// 检查当前登录账户的备份是否过期，并按设置刷新
+///
+/// 切换类操作执行期间跳过；检查时持有操作锁，避免与切换流程争用数据库
+pub fn check_live_account(app: &AppHandle, email: &str) {
+    if crate::account_switcher::is_operation_running() {
+        return;
+    }
+
+    let policy = crate::settings::get().backup_refresh;
+    if policy == BackupRefreshPolicy::Off {
+        return;
+    }
+
+    let stale =
+        crate::account_switcher::run_exclusive(|| antigravity_backup::stale_backup_keys(email));
+    let (entry, changed_keys) = match stale {
+        Ok(Some((entry, changed_keys))) if !changed_keys.is_empty() => (entry, changed_keys),
+        Ok(_) => return,
+        Err(e) => {
+            log::warn!("⚠️ 检查账户备份失败 ({}): {}", email, e);
+            return;
+        }
+    };
+
+    println!(
+        "🕒 账户 {} 的备份已过期，变化字段: {:?}",
+        entry.email, changed_keys
+    );
+
+    match policy {
+        BackupRefreshPolicy::Auto => refresh_backup(app, &entry.email, changed_keys),
+        BackupRefreshPolicy::Prompt => {
+            if PROMPT_OPEN.swap(true, SeqCst) {
+                return;
+            }
+
+            let app_handle = app.clone();
+            app.dialog()
+                .message(format!(
+                    "账户 {} 的登录信息已更新，已保存的备份已过期。\n是否用当前数据更新备份？",
+                    entry.email
+                ))
+                .title("更新账户备份")
+                .kind(MessageDialogKind::Info)
+                .buttons(MessageDialogButtons::OkCancelCustom(
+                    "更新".to_string(),
+                    "暂不".to_string(),
+                ))
+                .show(move |confirmed| {
+                    PROMPT_OPEN.store(false, SeqCst);
+                    if confirmed {
+                        refresh_backup(&app_handle, &entry.email, changed_keys);
+                    }
+                });
+        }
+        BackupRefreshPolicy::Off => {}
+    }
+}
@@ -3,49 +3,32 @@
 
 use rusqlite::Result as SqlResult;
 use serde_json::Value;
-use tauri::State;
+use tauri::{AppHandle, State};
 
-/// 切换 Antigravity 账户
+use crate::account_switcher::{OperationKind, SwitchOptions, SwitchReport};
+use crate::notifications::{notify_result, NotificationEvent};
+
+/// 切换 Antigravity 账户（按账户 ID 原地切换）
+///
+/// Antigravity 未运行时直接恢复账户数据；正在运行时执行完整的关闭、恢复、启动流程。
+/// `backup_current` 为空时按保存的切换选项决定是否先备份当前账户。
+/// 步骤失败或被取消时返回 `success = false` 的报告，只有无法开始执行时返回错误
 #[tauri::command]
 pub async fn switch_antigravity_account(
+    app: AppHandle,
     account_id: String,
+    backup_current: Option<bool>,
     _state: State<'_, crate::AppState>,
-) -> Result<String, String> {
+) -> Result<SwitchReport, String> {
     crate::log_async_command!("switch_antigravity_account", async {
-        // 获取 Antigravity 状态数据库路径
-        let app_data = match crate::platform_utils::get_antigravity_db_path() {
-            Some(path) => path,
-            None => {
-                // 如果主路径不存在，尝试其他可能的位置
-                let possible_paths = crate::platform_utils::get_all_antigravity_db_paths();
-                if possible_paths.is_empty() {
-                    return Err("未找到Antigravity安装位置".to_string());
-                }
-                possible_paths[0].clone()
-            }
-        };
-
-        if !app_data.exists() {
-            return Err(format!(
-                "Antigravity 状态数据库文件不存在: {}",
-                app_data.display()
-            ));
-        }
-
-        // 连接到 SQLite 数据库
-        let _conn = crate::Connection::open(&app_data)
-            .map_err(|e| format!("连接数据库失败 ({}): {}", app_data.display(), e))?;
-
-        // 记录数据库操作
-        crate::utils::log_decorator::log_database_operation("连接数据库", Some("ItemTable"), true);
-
-        // 这里应该加载并更新账户信息
-        // 由于状态管理的复杂性，我们先返回成功信息
-        Ok(format!(
-            "已切换到账户: {} (数据库: {})",
-            account_id,
-            app_data.display()
-        ))
+        crate::account_registry::validate_account_key(&account_id)?;
+        crate::account_switcher::run_operation(
+            Some(&app),
+            OperationKind::SwitchInPlace,
+            Some(account_id),
+            backup_current,
+        )
+        .await
     })
 }
 
@@ -54,9 +37,72 @@ pub async fn switch_antigravity_account(
 pub async fn get_antigravity_accounts(
     _state: State<'_, crate::AppState>,
 ) -> Result<Vec<crate::AntigravityAccount>, String> {
-    // 这里应该从存储中加载账户列表
-    // 暂时返回空列表
-    Ok(vec![])
+    let registry = crate::account_registry::load_registry()?;
+    let accounts_dir = crate::config_manager::ConfigManager::new()?.accounts_dir()?;
+
+    // 备份文件缺失的账户无法切换，不在列表中显示
+    Ok(registry
+        .accounts
+        .into_iter()
+        .filter(|entry| !entry.orphaned)
+        .map(|entry| {
+            // 头像以缩略图 data URL 的形式返回（备份文件未修改时使用缓存），解码失败时留空
+            let profile_url = crate::account_registry::backup_file_path(&accounts_dir, &entry)
+                .and_then(|backup_file| {
+                    crate::account_avatar::get_account_avatar(
+                        &entry,
+                        &backup_file,
+                        crate::account_avatar::DEFAULT_THUMBNAIL_SIZE,
+                        true,
+                    )
+                })
+                .ok()
+                .flatten()
+                .and_then(|avatar| avatar.data_url)
+                .unwrap_or_default();
+
+            crate::AntigravityAccount {
+                name: entry.aliases.first().cloned().unwrap_or(entry.file_name),
+                id: entry.id,
+                email: entry.email,
+                api_key: String::new(),
+                profile_url,
+                user_settings: String::new(),
+                created_at: entry.created_at,
+                last_switched: entry.last_switched.unwrap_or_default(),
+            }
+        })
+        .collect())
+}
+
+/// 获取所有账户的头像缩略图
+///
+/// `with_data_url` 为 true 时同时返回 data URL，便于前端直接显示
+#[tauri::command]
+pub async fn get_antigravity_account_avatars(
+    size: Option<u32>,
+    with_data_url: Option<bool>,
+) -> Result<Vec<crate::account_avatar::AccountAvatar>, String> {
+    crate::account_avatar::get_all_account_avatars(
+        size.unwrap_or(crate::account_avatar::DEFAULT_THUMBNAIL_SIZE),
+        with_data_url.unwrap_or(false),
+    )
+}
+
+/// 获取单个账户的头像缩略图（支持 ID / 邮箱 / 别名）
+#[tauri::command]
+pub async fn get_antigravity_account_avatar(
+    account: String,
+    size: Option<u32>,
+    with_data_url: Option<bool>,
+) -> Result<Option<crate::account_avatar::AccountAvatar>, String> {
+    let (entry, backup_file) = crate::account_registry::resolve_account(&account)?;
+    crate::account_avatar::get_account_avatar(
+        &entry,
+        &backup_file,
+        size.unwrap_or(crate::account_avatar::DEFAULT_THUMBNAIL_SIZE),
+        with_data_url.unwrap_or(false),
+    )
 }
 
 /// 获取当前 Antigravity 信息
@@ -114,9 +160,10 @@ pub async fn get_current_antigravity_info() -> Result<Value, String> {
 /// 备份当前 Antigravity 账户
 #[tauri::command]
 pub async fn backup_antigravity_current_account(
+    app: AppHandle,
     email: String, // 参数名改为 email，直接接收邮箱
 ) -> Result<String, String> {
-    crate::log_async_command!("backup_antigravity_current_account", async {
+    let result = crate::log_async_command!("backup_antigravity_current_account", async {
         log::info!("📥 开始备份账户: {}", email);
 
         // 直接调用智能备份函数，让它处理去重逻辑和文件名生成
@@ -132,92 +179,141 @@ pub async fn backup_antigravity_current_account(
                 Err(e)
             }
         }
-    })
+    });
+
+    notify_result(&app, NotificationEvent::Backup, &result);
+    result
 }
 
 /// 清除所有 Antigravity 数据
 #[tauri::command]
-pub async fn clear_all_antigravity_data() -> Result<String, String> {
-    crate::antigravity_cleanup::clear_all_antigravity_data().await
+pub async fn clear_all_antigravity_data(app: AppHandle) -> Result<String, String> {
+    let result = crate::antigravity_cleanup::clear_all_antigravity_data().await;
+    notify_result(&app, NotificationEvent::Logout, &result);
+    result
 }
 
 /// 恢复 Antigravity 账户
+///
+/// 持有操作锁执行，避免与托盘、定时或轮换切换同时写入数据库；
+/// Antigravity 正在运行时拒绝恢复（运行中请使用切换账户）
 #[tauri::command]
-pub async fn restore_antigravity_account(account_name: String) -> Result<String, String> {
-    println!(
-        "📥 调用 restore_antigravity_account，账户名: {}",
-        account_name
-    );
-
-    // 1. 构建备份文件路径
-    let config_dir = dirs::config_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join(".antigravity-agent")
-        .join("antigravity-accounts");
-    let backup_file = config_dir.join(format!("{}.json", account_name));
+pub async fn restore_antigravity_account(
+    app: AppHandle,
+    account_name: String,
+) -> Result<String, String> {
+    log::info!("📥 恢复账户: {}", account_name);
 
-    // 2. 调用统一的恢复函数
-    crate::antigravity_restore::restore_all_antigravity_data(backup_file).await
+    let result = crate::account_switcher::run_exclusive_async(async {
+        crate::account_registry::validate_account_key(&account_name)?;
+        if crate::platform_utils::is_antigravity_process_running() {
+            return Err("Antigravity 正在运行，请先关闭 Antigravity 或使用切换账户".to_string());
+        }
+        crate::account_switcher::restore_account(&account_name).await
+    })
+    .await
+    .map(|(_, restore_result)| restore_result);
+    notify_result(&app, NotificationEvent::Restore, &result);
+    result
 }
 
-/// 切换到 Antigravity 账户（调用 restore_antigravity_account）
+/// 重命名账户（账户 ID 不变，只修改备份文件名）
 #[tauri::command]
-pub async fn switch_to_antigravity_account(account_name: String) -> Result<String, String> {
-    crate::log_async_command!("switch_to_antigravity_account", async {
-        log::info!("🔄 开始执行切换到账户: {}", account_name);
+pub async fn rename_antigravity_account(
+    account: String,
+    new_name: String,
+) -> Result<crate::account_registry::AccountEntry, String> {
+    crate::log_async_command!("rename_antigravity_account", async {
+        crate::account_registry::validate_account_key(&account)?;
+        crate::account_registry::update_registry(|registry, accounts_dir| {
+            registry.rename(accounts_dir, &account, &new_name)
+        })
+    })
+}
 
-        // 1. 关闭 Antigravity 进程 (如果存在)
-        println!("🛑 步骤1: 检查并关闭 Antigravity 进程");
-        let kill_result = match crate::platform_utils::kill_antigravity_processes() {
-            Ok(result) => {
-                if result.contains("not found") || result.contains("未找到") {
-                    println!("ℹ️ Antigravity 进程未运行，跳过关闭步骤");
-                    "Antigravity 进程未运行".to_string()
-                } else {
-                    println!("✅ 进程关闭结果: {}", result);
-                    result
-                }
-            }
-            Err(e) => {
-                if e.contains("not found") || e.contains("未找到") {
-                    println!("ℹ️ Antigravity 进程未运行，跳过关闭步骤");
-                    "Antigravity 进程未运行".to_string()
-                } else {
-                    return Err(format!("关闭进程时发生错误: {}", e));
-                }
-            }
-        };
+/// 为账户添加别名
+#[tauri::command]
+pub async fn add_antigravity_account_alias(
+    account: String,
+    alias: String,
+) -> Result<crate::account_registry::AccountEntry, String> {
+    crate::account_registry::validate_account_key(&account)?;
+    crate::account_registry::update_registry(|registry, _| registry.add_alias(&account, &alias))
+}
 
-        // 等待一秒确保进程完全关闭
-        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+/// 移除账户别名
+#[tauri::command]
+pub async fn remove_antigravity_account_alias(
+    account: String,
+    alias: String,
+) -> Result<crate::account_registry::AccountEntry, String> {
+    crate::account_registry::validate_account_key(&account)?;
+    crate::account_registry::update_registry(|registry, _| registry.remove_alias(&account, &alias))
+}
 
-        // 2. 恢复指定账户到 Antigravity 数据库
-        println!("💾 步骤2: 恢复账户数据: {}", account_name);
-        let restore_result = restore_antigravity_account(account_name.clone()).await?;
-        println!("✅ 账户数据恢复完成: {}", restore_result);
+/// 查找同一身份的重复备份（邮箱忽略大小写后相同）
+#[tauri::command]
+pub async fn find_duplicate_antigravity_accounts(
+) -> Result<Vec<Vec<crate::account_registry::AccountEntry>>, String> {
+    Ok(crate::account_registry::load_registry()?.duplicate_groups())
+}
 
-        // 等待一秒确保数据库操作完成
-        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+/// 合并同一身份的两个备份，保留 `primary` 的账户 ID
+#[tauri::command]
+pub async fn merge_antigravity_accounts(
+    primary: String,
+    duplicate: String,
+) -> Result<crate::account_registry::AccountEntry, String> {
+    crate::log_async_command!("merge_antigravity_accounts", async {
+        crate::account_registry::validate_account_key(&primary)?;
+        crate::account_registry::validate_account_key(&duplicate)?;
+        crate::account_registry::update_registry(|registry, accounts_dir| {
+            registry.merge(accounts_dir, &primary, &duplicate)
+        })
+    })
+}
 
-        // 3. 重新启动 Antigravity 进程
-        println!("🚀 步骤3: 重新启动 Antigravity");
-        let start_result = crate::antigravity_starter::start_antigravity();
-        let start_message = match start_result {
-            Ok(result) => {
-                println!("✅ 启动结果: {}", result);
-                result
-            }
-            Err(e) => {
-                println!("⚠️ 启动失败: {}", e);
-                format!("启动失败: {}", e)
-            }
-        };
+/// 切换到 Antigravity 账户（关闭 -> 备份当前账户 -> 恢复 -> 校验 -> 重新启动）
+///
+/// 每个步骤的进度通过 `account-switch://progress` 事件上报；
+/// 步骤失败或被取消时返回 `success = false` 的报告，只有无法开始执行时返回错误
+#[tauri::command]
+pub async fn switch_to_antigravity_account(
+    app: AppHandle,
+    account_name: String,
+    backup_current: Option<bool>,
+) -> Result<SwitchReport, String> {
+    crate::log_async_command!("switch_to_antigravity_account", async {
+        log::info!("🔄 开始执行切换到账户: {}", account_name);
 
-        let final_message = format!("{} -> {} -> {}", kill_result, restore_result, start_message);
-        log::info!("🎉 账户切换完成: {}", final_message);
+        let report = crate::account_switcher::run_operation(
+            Some(&app),
+            OperationKind::Switch,
+            Some(account_name),
+            backup_current,
+        )
+        .await?;
+        log::info!("🎉 账户切换结束: {}", report.summary);
 
-        Ok(final_message)
+        Ok(report)
     })
 }
 
-// 命令函数将在后续步骤中移动到这里
+/// 获取账户切换选项
+#[tauri::command]
+pub async fn get_switch_options() -> Result<SwitchOptions, String> {
+    Ok(crate::settings::get().switch_options)
+}
+
+/// 保存账户切换选项
+#[tauri::command]
+pub async fn update_switch_options(options: SwitchOptions) -> Result<SwitchOptions, String> {
+    crate::settings::update(|settings| settings.switch_options = options)?;
+    Ok(options)
+}
+
+/// 取消正在进行的账户切换操作（在当前步骤结束后生效）
+#[tauri::command]
+pub async fn cancel_account_switch() -> Result<bool, String> {
+    Ok(crate::account_switcher::request_cancel())
+}
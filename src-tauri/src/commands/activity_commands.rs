@@ -0,0 +1,28 @@
+//! 账户使用统计命令
+//! 提供按天、按账户汇总的 Antigravity 使用时长查询
+
+use chrono::NaiveDate;
+
+/// 解析 YYYY-MM-DD 格式的日期参数
+fn parse_date(value: Option<String>) -> Result<Option<NaiveDate>, String> {
+    value
+        .filter(|v| !v.trim().is_empty())
+        .map(|v| {
+            NaiveDate::parse_from_str(v.trim(), "%Y-%m-%d")
+                .map_err(|e| format!("日期格式无效 ({}): {}", v, e))
+        })
+        .transpose()
+}
+
+/// 获取账户使用时长汇总
+///
+/// `from` / `to` 为可选的起止日期（包含首尾，格式 YYYY-MM-DD）
+#[tauri::command]
+pub async fn get_account_usage_summary(
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<crate::account_activity::UsageSummary, String> {
+    let from = parse_date(from)?;
+    let to = parse_date(to)?;
+    crate::account_activity::usage_summary(from, to)
+}
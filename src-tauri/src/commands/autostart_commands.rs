@@ -0,0 +1,34 @@
+//! 开机自启命令
+//! 负责开机自启和隐藏启动选项的读取与修改
+
+use tauri::AppHandle;
+
+use crate::autostart;
+
+/// 启用开机自启
+#[tauri::command]
+pub async fn enable_autostart(app: AppHandle) -> Result<String, String> {
+    autostart::set_enabled(&app, true)?;
+    Ok("已启用开机自启".to_string())
+}
+
+/// 禁用开机自启
+#[tauri::command]
+pub async fn disable_autostart(app: AppHandle) -> Result<String, String> {
+    autostart::set_enabled(&app, false)?;
+    Ok("已禁用开机自启".to_string())
+}
+
+/// 查询系统中是否已注册开机自启
+#[tauri::command]
+pub async fn is_autostart_enabled(app: AppHandle) -> Result<bool, String> {
+    autostart::is_enabled(&app)
+}
+
+/// 设置开机自启时是否直接隐藏到系统托盘
+#[tauri::command]
+pub async fn set_start_hidden(enabled: bool) -> Result<bool, String> {
+    crate::settings::update(|s| s.start_hidden = enabled)?;
+    log::info!("🙈 隐藏启动已{}", if enabled { "启用" } else { "禁用" });
+    Ok(enabled)
+}
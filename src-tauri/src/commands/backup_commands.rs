@@ -1,10 +1,9 @@
-use crate::AppState;
+use crate::config_manager::ConfigManager;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::path::Path;
 /// 备份相关命令
 /// 负责配置文件和账户的备份、恢复、删除等操作
-use tauri::State;
+use std::path::Path;
 
 /// 备份数据收集结构
 #[derive(Serialize, Deserialize, Debug)]
@@ -37,20 +36,15 @@ use zip::{write::FileOptions, ZipWriter};
 
 /// 创建配置文件备份
 #[tauri::command]
-pub async fn backup_profile(
-    name: String,
-    source_path: String,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
+pub async fn backup_profile(name: String, source_path: String) -> Result<String, String> {
     let source = Path::new(&source_path);
     if !source.exists() {
         return Err("源路径不存在".to_string());
     }
 
-    let backup_dir = state.config_dir.join("backups");
-    fs::create_dir_all(&backup_dir).map_err(|e| format!("创建备份目录失败: {}", e))?;
+    let backup_dir = ConfigManager::new()?.profile_backups_dir()?;
 
-    let backup_file = backup_dir.join(format!("{}.zip", name));
+    let backup_file = crate::account_registry::safe_join(&backup_dir, &format!("{}.zip", name))?;
 
     // 创建 ZIP 压缩文件
     let file = fs::File::create(&backup_file).map_err(|e| format!("创建备份文件失败: {}", e))?;
@@ -99,13 +93,9 @@ pub async fn backup_profile(
 
 /// 恢复配置文件备份
 #[tauri::command]
-pub async fn restore_profile(
-    name: String,
-    target_path: String,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let backup_dir = state.config_dir.join("backups");
-    let backup_file = backup_dir.join(format!("{}.zip", name));
+pub async fn restore_profile(name: String, target_path: String) -> Result<String, String> {
+    let backup_dir = ConfigManager::new()?.profile_backups_dir()?;
+    let backup_file = crate::account_registry::safe_join(&backup_dir, &format!("{}.zip", name))?;
 
     if !backup_file.exists() {
         return Err("备份文件不存在".to_string());
@@ -141,11 +131,11 @@ pub async fn restore_profile(
 
 /// 列出所有可用备份
 #[tauri::command]
-pub async fn list_backups(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+pub async fn list_backups() -> Result<Vec<String>, String> {
     let mut all_backups = Vec::new();
 
     // 只读取Antigravity账户目录中的JSON文件
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
+    let antigravity_dir = ConfigManager::new()?.accounts_dir()?;
 
     if antigravity_dir.exists() {
         for entry in
@@ -167,13 +157,11 @@ pub async fn list_backups(state: State<'_, AppState>) -> Result<Vec<String>, Str
 
 /// 收集所有备份文件的完整内容
 #[tauri::command]
-pub async fn collect_backup_contents(
-    state: State<'_, AppState>,
-) -> Result<Vec<BackupData>, String> {
+pub async fn collect_backup_contents() -> Result<Vec<BackupData>, String> {
     let mut backups_with_content = Vec::new();
 
     // 读取Antigravity账户目录中的JSON文件
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
+    let antigravity_dir = ConfigManager::new()?.accounts_dir()?;
 
     if !antigravity_dir.exists() {
         return Ok(backups_with_content);
@@ -224,26 +212,35 @@ pub async fn collect_backup_contents(
 
 /// 恢复备份文件到本地
 #[tauri::command]
-pub async fn restore_backup_files(
-    backups: Vec<BackupData>,
-    state: State<'_, AppState>,
-) -> Result<RestoreResult, String> {
+pub async fn restore_backup_files(backups: Vec<BackupData>) -> Result<RestoreResult, String> {
     let mut results = RestoreResult {
         restored_count: 0,
         failed: Vec::new(),
     };
 
-    // 获取目标目录
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
-
-    // 确保目录存在
-    if let Err(e) = fs::create_dir_all(&antigravity_dir) {
-        return Err(format!("创建目录失败: {}", e));
-    }
+    // 获取目标目录（不存在时自动创建）
+    let antigravity_dir = ConfigManager::new()?.accounts_dir()?;
 
     // 遍历每个备份
     for backup in backups {
-        let file_path = antigravity_dir.join(&backup.filename);
+        // 文件名来自调用方，必须是账户目录下的普通 JSON 文件名
+        let file_path = match crate::account_registry::safe_join(&antigravity_dir, &backup.filename)
+            .and_then(|path| {
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    Ok(path)
+                } else {
+                    Err(format!("备份文件必须是 JSON 文件: {}", backup.filename))
+                }
+            }) {
+            Ok(path) => path,
+            Err(e) => {
+                results.failed.push(FailedBackup {
+                    filename: backup.filename,
+                    error: e,
+                });
+                continue;
+            }
+        };
 
         match fs::write(
             &file_path,
@@ -268,23 +265,30 @@ pub async fn restore_backup_files(
 
 /// 删除指定备份
 #[tauri::command]
-pub async fn delete_backup(name: String, state: State<'_, AppState>) -> Result<String, String> {
-    // 只删除Antigravity账户JSON文件
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
-    let antigravity_file = antigravity_dir.join(format!("{}.json", name));
+pub async fn delete_backup(name: String) -> Result<String, String> {
+    // 通过账户注册表解析，只删除 Antigravity 账户 JSON 文件
+    crate::account_registry::validate_account_key(&name)?;
+
+    crate::account_registry::update_registry(|registry, accounts_dir| {
+        let entry = registry
+            .resolve(&name)
+            .cloned()
+            .ok_or_else(|| "用户文件不存在".to_string())?;
+        let antigravity_file = crate::account_registry::backup_file_path(accounts_dir, &entry)?;
+
+        if antigravity_file.exists() {
+            fs::remove_file(&antigravity_file).map_err(|e| format!("删除用户文件失败: {}", e))?;
+        }
+        registry.remove(&entry.id);
 
-    if antigravity_file.exists() {
-        fs::remove_file(&antigravity_file).map_err(|e| format!("删除用户文件失败: {}", e))?;
         Ok(format!("删除用户成功: {}", name))
-    } else {
-        Err("用户文件不存在".to_string())
-    }
+    })
 }
 
 /// 清空所有备份
 #[tauri::command]
-pub async fn clear_all_backups(state: State<'_, AppState>) -> Result<String, String> {
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
+pub async fn clear_all_backups() -> Result<String, String> {
+    let antigravity_dir = ConfigManager::new()?.accounts_dir()?;
 
     if antigravity_dir.exists() {
         // 读取目录中的所有文件
@@ -303,6 +307,12 @@ pub async fn clear_all_backups(state: State<'_, AppState>) -> Result<String, Str
             }
         }
 
+        // 同步清空账户注册表
+        crate::account_registry::update_registry(|registry, _| {
+            registry.accounts.clear();
+            Ok(())
+        })?;
+
         Ok(format!(
             "已清空所有用户备份，共删除 {} 个文件",
             deleted_count
@@ -313,3 +323,18 @@ pub async fn clear_all_backups(state: State<'_, AppState>) -> Result<String, Str
 }
 
 // 备份相关函数将在后续步骤中移动到这里
+
+/// 获取备份过期时的处理方式
+#[tauri::command]
+pub async fn get_backup_refresh_policy(
+) -> Result<crate::backup_freshness::BackupRefreshPolicy, String> {
+    Ok(crate::settings::get().backup_refresh)
+}
+
+/// 设置备份过期时的处理方式（auto / prompt / off）
+#[tauri::command]
+pub async fn set_backup_refresh_policy(
+    policy: crate::backup_freshness::BackupRefreshPolicy,
+) -> Result<(), String> {
+    crate::settings::update(|settings| settings.backup_refresh = policy).map(|_| ())
+}
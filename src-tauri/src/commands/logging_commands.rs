@@ -5,6 +5,9 @@ use dirs;
 use regex::Regex;
 use std::fs;
 
+use crate::config_manager::ConfigManager;
+use crate::constants::paths;
+
 // 日志文件大小限制 (10MB)
 const MAX_LOG_FILE_SIZE: u64 = 10 * 1024 * 1024;
 
@@ -139,14 +142,8 @@ pub async fn get_log_content() -> Result<String, String> {
     crate::log_async_command!("get_log_content", async {
         log::info!("📤 请求日志内容");
 
-        // 获取日志目录 - 使用系统配置目录
-        let log_dir = dirs::config_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("antigravity-agent")
-            .join("logs");
-
         // 检查日志文件是否存在
-        let log_file = log_dir.join("antigravity-agent.log");
+        let log_file = ConfigManager::new()?.log_file()?;
         if !log_file.exists() {
             log::warn!("日志文件不存在");
             return Err("日志文件不存在".to_string());
@@ -226,12 +223,7 @@ pub async fn export_logs() -> Result<String, String> {
 /// 返回日志文件路径、大小等信息，用于前端显示状态
 #[tauri::command]
 pub async fn get_log_info() -> Result<LogInfo, String> {
-    let log_dir = dirs::config_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("antigravity-agent")
-        .join("logs");
-
-    let log_file = log_dir.join("antigravity-agent.log");
+    let log_file = ConfigManager::new()?.log_file()?;
 
     if log_file.exists() {
         let metadata = fs::metadata(&log_file).map_err(|e| format!("获取文件信息失败: {}", e))?;
@@ -267,12 +259,8 @@ pub async fn get_log_info() -> Result<LogInfo, String> {
 #[tauri::command]
 pub async fn clear_logs() -> Result<String, String> {
     crate::log_async_command!("clear_logs", async {
-        let log_dir = dirs::config_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("antigravity-agent")
-            .join("logs");
-
-        let log_file = log_dir.join("antigravity-agent.log");
+        let log_dir = ConfigManager::new()?.logs_dir()?;
+        let log_file = log_dir.join(paths::LOG_FILE);
 
         if log_file.exists() {
             // 备份当前日志（可选）
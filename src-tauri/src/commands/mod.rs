@@ -21,10 +21,34 @@ pub mod tray_commands;
 // 日志相关命令
 pub mod logging_commands;
 
+// 账户使用统计命令
+pub mod activity_commands;
+
+// 账户轮换命令
+pub mod rotation_commands;
+
+// 定时任务命令
+pub mod scheduler_commands;
+
+// 系统通知命令
+pub mod notification_commands;
+
+// 应用设置命令
+pub mod settings_commands;
+
+// 开机自启命令
+pub mod autostart_commands;
+
 // 重新导出所有命令，保持与 main.rs 的兼容性
 pub use account_commands::*;
+pub use activity_commands::*;
+pub use autostart_commands::*;
 pub use backup_commands::*;
 pub use logging_commands::*;
+pub use notification_commands::*;
 pub use platform_commands::*;
 pub use process_commands::*;
+pub use rotation_commands::*;
+pub use scheduler_commands::*;
+pub use settings_commands::*;
 pub use tray_commands::*;
@@ -0,0 +1,20 @@
+//! 系统通知命令
+//! 负责各类操作结果通知开关的读取和保存
+
+use crate::notifications::NotificationSettings;
+
+/// 获取通知开关设置
+#[tauri::command]
+pub async fn get_notification_settings() -> Result<NotificationSettings, String> {
+    Ok(crate::settings::get().notifications)
+}
+
+/// 保存通知开关设置
+#[tauri::command]
+pub async fn set_notification_settings(
+    settings: NotificationSettings,
+) -> Result<NotificationSettings, String> {
+    crate::settings::update(|s| s.notifications = settings)?;
+    log::info!("🔔 通知设置已更新: {:?}", settings);
+    Ok(settings)
+}
@@ -12,6 +12,7 @@ pub async fn get_platform_info() -> Result<Value, String> {
 
     let antigravity_available = crate::platform_utils::is_antigravity_available();
     let antigravity_paths = crate::platform_utils::get_all_antigravity_db_paths();
+    let config_manager = crate::config_manager::ConfigManager::new().ok();
 
     Ok(serde_json::json!({
         "os": os_type,
@@ -20,6 +21,10 @@ pub async fn get_platform_info() -> Result<Value, String> {
         "antigravity_available": antigravity_available,
         "antigravity_paths": antigravity_paths.iter().map(|p| p.to_string_lossy()).collect::<Vec<_>>(),
         "config_dir": dirs::config_dir().map(|p| p.to_string_lossy().to_string()),
+        "agent_config_dir": config_manager
+            .as_ref()
+            .map(|c| c.config_dir().to_string_lossy().to_string()),
+        "agent_data_dir_source": config_manager.as_ref().map(|c| c.data_dir_source()),
         "data_dir": dirs::data_dir().map(|p| p.to_string_lossy().to_string()),
         "home_dir": dirs::home_dir().map(|p| p.to_string_lossy().to_string())
     }))
@@ -65,14 +70,17 @@ pub async fn save_antigravity_path(path: String) -> Result<(), String> {
     crate::platform_utils::persist_antigravity_path(&path_buf)
 }
 
-/// 检查 Antigravity 进程是否运行（仅 Windows）
+/// 检查 Antigravity 进程是否运行
 #[tauri::command]
 pub async fn is_antigravity_running() -> Result<bool, String> {
-    if !cfg!(windows) {
-        return Ok(false);
-    }
-
     Ok(crate::platform_utils::is_antigravity_process_running())
 }
 
+/// 获取运行中的 Antigravity 进程列表（PID、可执行文件、运行时长）
+#[tauri::command]
+pub async fn get_antigravity_processes(
+) -> Result<Vec<crate::antigravity_process::AntigravityProcess>, String> {
+    Ok(crate::antigravity_process::find_antigravity_processes())
+}
+
 // 命令函数将在后续步骤中移动到这里
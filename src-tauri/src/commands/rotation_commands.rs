@@ -0,0 +1,82 @@
+//! 账户轮换命令
+//! 负责轮换顺序、账户停用 / 过期 / 冷却标记，以及切换到下一个 / 上一个 / 上一次使用的账户
+
+use tauri::AppHandle;
+
+use crate::account_registry::{self, AccountEntry, RotationDirection};
+use crate::account_switcher::SwitchReport;
+
+/// 按方向执行轮换切换，复用完整的关闭、备份、恢复、启动流程
+///
+/// 步骤失败或被取消时返回 `success = false` 的报告
+async fn switch_in_rotation(
+    app: &AppHandle,
+    direction: RotationDirection,
+) -> Result<SwitchReport, String> {
+    crate::account_switcher::switch_in_rotation(Some(app), direction).await
+}
+
+/// 切换到轮换顺序中的下一个可用账户
+#[tauri::command]
+pub async fn switch_to_next_account(app: AppHandle) -> Result<SwitchReport, String> {
+    crate::log_async_command!("switch_to_next_account", async {
+        switch_in_rotation(&app, RotationDirection::Next).await
+    })
+}
+
+/// 切换到轮换顺序中的上一个可用账户
+#[tauri::command]
+pub async fn switch_to_previous_account(app: AppHandle) -> Result<SwitchReport, String> {
+    crate::log_async_command!("switch_to_previous_account", async {
+        switch_in_rotation(&app, RotationDirection::Previous).await
+    })
+}
+
+/// 切换回上一次使用的账户（根据切换历史，反复调用可在两个账户间来回切换）
+#[tauri::command]
+pub async fn switch_to_last_account(app: AppHandle) -> Result<SwitchReport, String> {
+    crate::log_async_command!("switch_to_last_account", async {
+        switch_in_rotation(&app, RotationDirection::Last).await
+    })
+}
+
+/// 获取按轮换顺序排列的账户
+#[tauri::command]
+pub async fn get_account_rotation() -> Result<Vec<AccountEntry>, String> {
+    let registry = account_registry::load_registry()?;
+    Ok(registry.rotation().into_iter().cloned().collect())
+}
+
+/// 设置轮换顺序（传入空列表时恢复为按注册顺序轮换全部账户）
+#[tauri::command]
+pub async fn set_account_rotation(accounts: Vec<String>) -> Result<Vec<AccountEntry>, String> {
+    account_registry::update_registry(|registry, _| registry.set_rotation_order(&accounts))
+}
+
+/// 设置账户的停用 / 过期标记，未传入的标记保持不变
+#[tauri::command]
+pub async fn set_account_flags(
+    account: String,
+    disabled: Option<bool>,
+    expired: Option<bool>,
+) -> Result<AccountEntry, String> {
+    account_registry::update_registry(|registry, _| registry.set_flags(&account, disabled, expired))
+}
+
+/// 清除账户的冷却状态
+#[tauri::command]
+pub async fn clear_account_cooldown(account: String) -> Result<AccountEntry, String> {
+    account_registry::update_registry(|registry, _| registry.set_cooldown(&account, None, None))
+}
+
+/// 获取检测到配额 / 限流 / 认证错误后的处理方式
+#[tauri::command]
+pub async fn get_quota_action() -> Result<crate::log_monitor::QuotaAction, String> {
+    Ok(crate::settings::get().quota_action)
+}
+
+/// 设置检测到配额 / 限流 / 认证错误后的处理方式（off / suggest / switch）
+#[tauri::command]
+pub async fn set_quota_action(action: crate::log_monitor::QuotaAction) -> Result<(), String> {
+    crate::settings::update(|settings| settings.quota_action = action).map(|_| ())
+}
@@ -0,0 +1,39 @@
+//! 定时任务命令
+//! 负责定时切换账户、定时备份等任务的增删改查
+
+use crate::scheduler::{self, JobAction, JobTrigger, ScheduledJob};
+
+/// 获取所有定时任务
+#[tauri::command]
+pub async fn list_scheduled_jobs() -> Result<Vec<ScheduledJob>, String> {
+    scheduler::list_jobs()
+}
+
+/// 添加定时任务
+#[tauri::command]
+pub async fn add_scheduled_job(
+    name: Option<String>,
+    action: JobAction,
+    trigger: JobTrigger,
+    enabled: Option<bool>,
+) -> Result<ScheduledJob, String> {
+    scheduler::add_job(name, action, trigger, enabled.unwrap_or(true))
+}
+
+/// 更新定时任务，未传入的字段保持不变
+#[tauri::command]
+pub async fn update_scheduled_job(
+    id: String,
+    name: Option<String>,
+    action: Option<JobAction>,
+    trigger: Option<JobTrigger>,
+    enabled: Option<bool>,
+) -> Result<ScheduledJob, String> {
+    scheduler::update_job(&id, name, action, trigger, enabled)
+}
+
+/// 删除定时任务
+#[tauri::command]
+pub async fn remove_scheduled_job(id: String) -> Result<(), String> {
+    scheduler::remove_job(&id)
+}
@@ -0,0 +1,60 @@
+//! 应用设置命令
+//! 负责读取和修改统一的应用设置
+
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::settings::{self, AgentSettings};
+
+/// 获取全部设置
+#[tauri::command]
+pub async fn get_settings() -> Result<AgentSettings, String> {
+    Ok(settings::get())
+}
+
+/// 修改设置，只需传入要修改的字段（嵌套对象按字段合并）
+///
+/// 修改成功后发送 `settings://changed` 事件；系统托盘开关变化时立即创建或移除托盘图标，
+/// 开机自启开关变化时同步注册或移除系统中的自启项
+#[tauri::command]
+pub async fn update_settings(app: AppHandle, patch: Value) -> Result<AgentSettings, String> {
+    let previous = settings::get();
+    let updated = settings::update_from_patch(patch)?;
+    log::info!("⚙️ 设置已更新");
+
+    if updated.system_tray_enabled != previous.system_tray_enabled {
+        apply_system_tray(updated.system_tray_enabled)?;
+    }
+
+    if updated.launch_at_login != previous.launch_at_login {
+        if let Err(e) = crate::autostart::register(&app, updated.launch_at_login) {
+            // 系统中的自启项没有变化，设置也恢复原值
+            settings::update(|s| s.launch_at_login = previous.launch_at_login)?;
+            return Err(e);
+        }
+    }
+
+    Ok(updated)
+}
+
+/// 按设置创建或移除系统托盘图标
+fn apply_system_tray(enabled: bool) -> Result<(), String> {
+    let manager = crate::system_tray::SystemTrayManager::get_global()
+        .ok_or("系统托盘未初始化".to_string())?;
+
+    {
+        let mut manager = manager
+            .lock()
+            .map_err(|_| "系统托盘管理器不可用（可能正在维护中）".to_string())?;
+        let result = if enabled {
+            manager.enable()
+        } else {
+            manager.disable()
+        };
+        result.map_err(|e| format!("更新系统托盘失败: {}", e))?;
+    }
+
+    // 释放锁后再更新新图标的状态角标
+    crate::system_tray::SystemTrayManager::refresh_status();
+    Ok(())
+}
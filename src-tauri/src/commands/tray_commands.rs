@@ -1,38 +1,44 @@
 //! 系统托盘命令
 //! 负责系统托盘的启用、禁用、最小化、恢复等操作
 
-/// 启用系统托盘
+/// 启用系统托盘（创建托盘图标并保存偏好）
 #[tauri::command]
 pub async fn enable_system_tray() -> Result<String, String> {
     if let Some(manager) = crate::system_tray::SystemTrayManager::get_global() {
         // 安全的锁获取，避免毒化锁 panic
         match manager.lock() {
-            Ok(mut manager) => match manager.enable() {
-                Ok(_) => Ok("系统托盘功能已启用".to_string()),
-                Err(e) => Err(format!("启用系统托盘失败: {}", e)),
-            },
-            Err(_) => Err("系统托盘管理器不可用（可能正在维护中）".to_string()),
+            Ok(mut manager) => manager
+                .enable()
+                .map_err(|e| format!("启用系统托盘失败: {}", e))?,
+            Err(_) => return Err("系统托盘管理器不可用（可能正在维护中）".to_string()),
         }
     } else {
-        Err("系统托盘未初始化".to_string())
+        return Err("系统托盘未初始化".to_string());
     }
+
+    // 释放锁后再更新新图标的状态角标
+    crate::system_tray::SystemTrayManager::refresh_status();
+    crate::window_state_manager::save_system_tray_state(true).await?;
+    Ok("系统托盘功能已启用".to_string())
 }
 
-/// 禁用系统托盘
+/// 禁用系统托盘（移除托盘图标并保存偏好）
 #[tauri::command]
 pub async fn disable_system_tray() -> Result<String, String> {
     if let Some(manager) = crate::system_tray::SystemTrayManager::get_global() {
         // 安全的锁获取，避免毒化锁 panic
         match manager.lock() {
-            Ok(mut manager) => match manager.disable() {
-                Ok(_) => Ok("系统托盘功能已禁用".to_string()),
-                Err(e) => Err(format!("禁用系统托盘失败: {}", e)),
-            },
-            Err(_) => Err("系统托盘管理器不可用（可能正在维护中）".to_string()),
+            Ok(mut manager) => manager
+                .disable()
+                .map_err(|e| format!("禁用系统托盘失败: {}", e))?,
+            Err(_) => return Err("系统托盘管理器不可用（可能正在维护中）".to_string()),
         }
     } else {
-        Err("系统托盘未初始化".to_string())
+        return Err("系统托盘未初始化".to_string());
     }
+
+    crate::window_state_manager::save_system_tray_state(false).await?;
+    Ok("系统托盘功能已禁用".to_string())
 }
 
 /// 最小化到托盘
@@ -1,28 +1,371 @@
 use crate::constants::paths;
 /// 配置管理器
-/// 统一管理所有配置目录和文件路径
+/// 统一管理所有配置目录和文件路径，所有模块都通过这里获取 Agent 自身数据的存放位置
+use once_cell::sync::Lazy;
+use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// 配置管理器结构
 pub struct ConfigManager {
     config_dir: PathBuf,
 }
 
+/// 数据目录的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataDirSource {
+    /// 命令行参数 `--data-dir`
+    CommandLine,
+    /// 环境变量 `ANTIGRAVITY_AGENT_DATA_DIR`
+    Environment,
+    /// 便携模式（`--portable` 参数或可执行文件旁的 `portable` 标记文件）
+    Portable,
+    /// 系统配置目录
+    Default,
+}
+
+/// 启动时解析一次的数据目录，运行期间保持不变
+static DATA_DIR: Lazy<(PathBuf, DataDirSource)> = Lazy::new(resolve_config_dir);
+
+/// 相对路径按当前工作目录解析
+fn absolute(path: PathBuf) -> PathBuf {
+    if path.is_absolute() {
+        path
+    } else {
+        std::env::current_dir()
+            .map(|cwd| cwd.join(&path))
+            .unwrap_or(path)
+    }
+}
+
+/// 从命令行参数中读取 `--data-dir`
+fn data_dir_from_args() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == paths::DATA_DIR_ARG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(value) = arg.strip_prefix(&format!("{}=", paths::DATA_DIR_ARG)) {
+            return Some(PathBuf::from(value));
+        }
+    }
+    None
+}
+
+/// 解析 Agent 数据目录，优先级：`--data-dir` 参数 > 环境变量 > 便携模式 > 系统配置目录
+///
+/// 便携模式下数据保存在可执行文件旁的 `data` 目录中；默认位置为系统配置目录下的
+/// `.antigravity-agent`（Windows 为 `%APPDATA%\.antigravity-agent`，macOS 为
+/// `~/Library/Application Support/.antigravity-agent`，Linux 为 `~/.config/.antigravity-agent`）
+fn resolve_config_dir() -> (PathBuf, DataDirSource) {
+    if let Some(dir) = data_dir_from_args().filter(|p| !p.as_os_str().is_empty()) {
+        return (absolute(dir), DataDirSource::CommandLine);
+    }
+
+    if let Some(dir) = std::env::var_os(paths::DATA_DIR_ENV).filter(|v| !v.is_empty()) {
+        return (absolute(PathBuf::from(dir)), DataDirSource::Environment);
+    }
+
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    if let Some(exe_dir) = exe_dir {
+        let portable = std::env::args().any(|arg| arg == paths::PORTABLE_ARG)
+            || exe_dir.join(paths::PORTABLE_MARKER_FILE).exists();
+        if portable {
+            return (
+                exe_dir.join(paths::PORTABLE_DATA_DIR_NAME),
+                DataDirSource::Portable,
+            );
+        }
+    }
+
+    let dir = dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(paths::CONFIG_DIR_NAME);
+    (dir, DataDirSource::Default)
+}
+
 impl ConfigManager {
     /// 创建新的配置管理器
     pub fn new() -> Result<Self, String> {
-        let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join(paths::CONFIG_DIR_NAME);
+        let config_dir = DATA_DIR.0.clone();
 
         fs::create_dir_all(&config_dir).map_err(|e| format!("创建配置目录失败: {}", e))?;
 
         Ok(Self { config_dir })
     }
 
+    /// 获取配置根目录
+    pub fn config_dir(&self) -> &Path {
+        &self.config_dir
+    }
+
+    /// 获取配置根目录的来源
+    pub fn data_dir_source(&self) -> DataDirSource {
+        DATA_DIR.1
+    }
+
+    /// 获取应用设置文件路径
+    pub fn settings_file(&self) -> PathBuf {
+        self.config_dir.join(paths::SETTINGS_FILE)
+    }
+
     /// 获取窗口状态文件路径
     pub fn window_state_file(&self) -> PathBuf {
         self.config_dir.join(paths::WINDOW_STATE_FILE)
     }
+
+    /// 获取账户备份目录（不存在时自动创建）
+    pub fn accounts_dir(&self) -> Result<PathBuf, String> {
+        let dir = self.config_dir.join(paths::ACCOUNTS_DIR_NAME);
+        fs::create_dir_all(&dir).map_err(|e| format!("创建账户目录失败: {}", e))?;
+        Ok(dir)
+    }
+
+    /// 获取账户头像缩略图缓存目录（不存在时自动创建）
+    pub fn avatar_cache_dir(&self) -> Result<PathBuf, String> {
+        let dir = self.config_dir.join(paths::AVATAR_CACHE_DIR_NAME);
+        fs::create_dir_all(&dir).map_err(|e| format!("创建头像缓存目录失败: {}", e))?;
+        Ok(dir)
+    }
+
+    /// 获取配置文件备份（ZIP）目录（不存在时自动创建）
+    pub fn profile_backups_dir(&self) -> Result<PathBuf, String> {
+        let dir = self.config_dir.join(paths::PROFILE_BACKUPS_DIR_NAME);
+        fs::create_dir_all(&dir).map_err(|e| format!("创建备份目录失败: {}", e))?;
+        Ok(dir)
+    }
+
+    /// 获取日志目录（不存在时自动创建）
+    pub fn logs_dir(&self) -> Result<PathBuf, String> {
+        let dir = self.config_dir.join(paths::LOGS_DIR_NAME);
+        fs::create_dir_all(&dir).map_err(|e| format!("创建日志目录失败: {}", e))?;
+        Ok(dir)
+    }
+
+    /// 获取日志文件路径
+    pub fn log_file(&self) -> Result<PathBuf, String> {
+        Ok(self.logs_dir()?.join(paths::LOG_FILE))
+    }
+
+    /// 获取账户使用会话记录文件路径
+    pub fn activity_file(&self) -> PathBuf {
+        self.config_dir.join(paths::ACTIVITY_FILE)
+    }
+
+    /// 获取定时任务文件路径
+    pub fn schedule_file(&self) -> PathBuf {
+        self.config_dir.join(paths::SCHEDULE_FILE)
+    }
+
+    /// 获取账户注册表文件路径
+    pub fn account_registry_file(&self) -> PathBuf {
+        self.config_dir.join(paths::ACCOUNT_REGISTRY_FILE)
+    }
+
+    /// 把旧版本分散在其他位置的数据迁移到当前配置目录（只在首次启动新版本时执行）
+    ///
+    /// 旧版本中账户备份（`antigravity-accounts`）、`config.json`、窗口状态和配置文件备份都保存在
+    /// `<系统配置目录>/.antigravity-agent` 下，与当前位置相同，只有以下位置需要迁移：
+    /// - Windows 下 `AppState` 按 `APPDATA` 环境变量（或用户目录下的 `AppData\Roaming`）拼出的
+    ///   `.antigravity-agent`，`APPDATA` 被重定向时与系统配置目录不同
+    /// - 系统配置目录下没有前缀点的 `antigravity-agent/logs`
+    ///
+    /// 目标位置已存在的文件保持不变。只有全部迁移成功才写入完成标记，失败的部分在下次启动时重试。
+    /// 在日志初始化之前调用，返回迁移记录供之后写入日志。
+    /// 通过参数、环境变量或便携模式指定了数据目录时不迁移，避免把系统目录中的数据搬走
+    pub fn migrate_legacy_locations(&self) -> Vec<String> {
+        let marker = self.config_dir.join(paths::MIGRATION_MARKER_FILE);
+        if self.data_dir_source() != DataDirSource::Default || marker.exists() {
+            return Vec::new();
+        }
+
+        let mut notes = Vec::new();
+        let mut complete = true;
+        for legacy_root in legacy_config_dirs() {
+            if same_dir(&legacy_root, &self.config_dir) || !legacy_root.is_dir() {
+                continue;
+            }
+            notes.push(format!("迁移旧配置目录: {}", legacy_root.display()));
+            complete &= move_contents(&legacy_root, &self.config_dir, &mut notes);
+            remove_if_empty(&legacy_root);
+        }
+
+        if let Some(legacy_logs_parent) =
+            dirs::config_dir().map(|dir| dir.join(paths::LEGACY_LOGS_PARENT_DIR_NAME))
+        {
+            let legacy_logs = legacy_logs_parent.join(paths::LOGS_DIR_NAME);
+            if legacy_logs.is_dir() {
+                notes.push(format!("迁移旧日志目录: {}", legacy_logs.display()));
+                complete &= move_contents(
+                    &legacy_logs,
+                    &self.config_dir.join(paths::LOGS_DIR_NAME),
+                    &mut notes,
+                );
+                remove_if_empty(&legacy_logs);
+                remove_if_empty(&legacy_logs_parent);
+            }
+        }
+
+        if !complete {
+            notes.push("部分旧数据迁移失败，将在下次启动时重试".to_string());
+        } else if let Err(e) = fs::write(&marker, chrono::Local::now().to_rfc3339()) {
+            notes.push(format!("写入迁移标记失败: {}", e));
+        }
+        notes
+    }
+}
+
+/// 旧版本可能使用的配置目录
+fn legacy_config_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if cfg!(windows) {
+        if let Some(appdata) = std::env::var_os("APPDATA") {
+            dirs.push(PathBuf::from(appdata).join(paths::CONFIG_DIR_NAME));
+        }
+        if let Some(home) = dirs::home_dir() {
+            dirs.push(
+                home.join("AppData")
+                    .join("Roaming")
+                    .join(paths::CONFIG_DIR_NAME),
+            );
+        }
+    }
+    dirs
+}
+
+/// 判断两个路径是否指向同一目录
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// 把目录中的内容移动到目标目录，子目录递归合并，目标已存在的文件跳过
+///
+/// 返回是否全部迁移成功（跳过已存在的文件不算失败）
+fn move_contents(from: &Path, to: &Path, notes: &mut Vec<String>) -> bool {
+    if let Err(e) = fs::create_dir_all(to) {
+        notes.push(format!("创建目录 {} 失败: {}", to.display(), e));
+        return false;
+    }
+
+    let entries = match fs::read_dir(from) {
+        Ok(entries) => entries,
+        Err(e) => {
+            notes.push(format!("读取目录 {} 失败: {}", from.display(), e));
+            return false;
+        }
+    };
+
+    let mut complete = true;
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                notes.push(format!("读取目录 {} 失败: {}", from.display(), e));
+                complete = false;
+                continue;
+            }
+        };
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+
+        if source.is_dir() {
+            if target.exists() {
+                complete &= move_contents(&source, &target, notes);
+                remove_if_empty(&source);
+                continue;
+            }
+        } else if target.exists() {
+            notes.push(format!("已存在，保留当前文件: {}", target.display()));
+            continue;
+        }
+
+        if let Err(e) = move_path(&source, &target) {
+            notes.push(format!("迁移 {} 失败: {}", source.display(), e));
+            complete = false;
+        }
+    }
+    complete
+}
+
+/// 移动文件或目录，跨磁盘无法重命名时复制后删除
+fn move_path(source: &Path, target: &Path) -> Result<(), String> {
+    if fs::rename(source, target).is_ok() {
+        return Ok(());
+    }
+
+    if source.is_dir() {
+        fs::create_dir_all(target).map_err(|e| e.to_string())?;
+        for entry in fs::read_dir(source).map_err(|e| e.to_string())?.flatten() {
+            move_path(&entry.path(), &target.join(entry.file_name()))?;
+        }
+        fs::remove_dir(source).map_err(|e| e.to_string())
+    } else {
+        fs::copy(source, target).map_err(|e| e.to_string())?;
+        fs::remove_file(source).map_err(|e| e.to_string())
+    }
+}
+
+/// 删除空目录（非空或删除失败时忽略）
+fn remove_if_empty(dir: &Path) {
+    let _ = fs::remove_dir(dir);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir;
+
+    #[test]
+    fn move_contents_merges_without_overwriting() {
+        let root = temp_dir("migrate");
+        let (from, to) = (root.join("legacy"), root.join("current"));
+        fs::create_dir_all(from.join("antigravity-accounts")).unwrap();
+        fs::create_dir_all(to.join("antigravity-accounts")).unwrap();
+        fs::write(from.join("config.json"), "legacy").unwrap();
+        fs::write(from.join("window_state.json"), "legacy").unwrap();
+        fs::write(from.join("antigravity-accounts/a.json"), "a").unwrap();
+        fs::write(to.join("config.json"), "current").unwrap();
+
+        let mut notes = Vec::new();
+        assert!(move_contents(&from, &to, &mut notes));
+
+        assert_eq!(
+            fs::read_to_string(to.join("config.json")).unwrap(),
+            "current"
+        );
+        assert_eq!(
+            fs::read_to_string(to.join("window_state.json")).unwrap(),
+            "legacy"
+        );
+        assert_eq!(
+            fs::read_to_string(to.join("antigravity-accounts/a.json")).unwrap(),
+            "a"
+        );
+        assert!(!from.join("window_state.json").exists());
+        assert!(!from.join("antigravity-accounts").exists());
+        // 目标已存在的旧文件保留在原处
+        assert!(from.join("config.json").exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn move_contents_reports_unreadable_source() {
+        let root = temp_dir("migrate-missing");
+        let mut notes = Vec::new();
+        assert!(!move_contents(
+            &root.join("missing"),
+            &root.join("current"),
+            &mut notes
+        ));
+        assert_eq!(notes.len(), 1);
+
+        let _ = fs::remove_dir_all(&root);
+    }
 }
@@ -43,6 +43,9 @@ pub mod database {
         NEW_STORAGE_MARKER,
     ];
 
+    /// 用于判断已保存的备份是否过期的字段（认证信息和用户设置）
+    pub const FRESHNESS_KEYS: &[&str] = &[AUTH_STATUS, USER_SETTINGS];
+
     /// 需要清除的字段列表（不包含会话数据）
     pub const DELETE_KEYS: &[&str] = &[
         AUTH_STATUS,
@@ -59,8 +62,59 @@ pub mod paths {
     /// 配置目录名称
     pub const CONFIG_DIR_NAME: &str = ".antigravity-agent";
 
+    /// 应用设置文件
+    pub const SETTINGS_FILE: &str = "config.json";
+
     /// 窗口状态文件
     pub const WINDOW_STATE_FILE: &str = "window_state.json";
+
+    /// 账户备份目录
+    pub const ACCOUNTS_DIR_NAME: &str = "antigravity-accounts";
+
+    /// 账户注册表文件（位于账户备份目录之外，避免被当作账户备份扫描）
+    pub const ACCOUNT_REGISTRY_FILE: &str = "accounts_registry.json";
+
+    /// 账户头像缩略图缓存目录
+    pub const AVATAR_CACHE_DIR_NAME: &str = "avatar-cache";
+
+    /// 账户使用会话记录文件
+    pub const ACTIVITY_FILE: &str = "account_activity.json";
+
+    /// 定时任务文件
+    pub const SCHEDULE_FILE: &str = "scheduled_jobs.json";
+
+    /// 配置文件备份（ZIP）目录
+    pub const PROFILE_BACKUPS_DIR_NAME: &str = "backups";
+
+    /// 日志目录
+    pub const LOGS_DIR_NAME: &str = "logs";
+
+    /// 日志文件
+    pub const LOG_FILE: &str = "antigravity-agent.log";
+
+    /// 旧版本日志所在的目录名称（位于系统配置目录下，没有前缀点）
+    pub const LEGACY_LOGS_PARENT_DIR_NAME: &str = "antigravity-agent";
+
+    /// 旧版数据迁移完成标记
+    pub const MIGRATION_MARKER_FILE: &str = ".legacy-migrated";
+
+    /// 指定数据目录的环境变量
+    pub const DATA_DIR_ENV: &str = "ANTIGRAVITY_AGENT_DATA_DIR";
+
+    /// 指定数据目录的命令行参数（`--data-dir <路径>` 或 `--data-dir=<路径>`）
+    pub const DATA_DIR_ARG: &str = "--data-dir";
+
+    /// 以便携模式启动的命令行参数
+    pub const PORTABLE_ARG: &str = "--portable";
+
+    /// 开机自启时附加的命令行参数，用于区分用户手动启动
+    pub const AUTOSTART_ARG: &str = "--autostart";
+
+    /// 可执行文件旁的便携模式标记文件
+    pub const PORTABLE_MARKER_FILE: &str = "portable";
+
+    /// 便携模式下的数据目录名称（位于可执行文件旁）
+    pub const PORTABLE_DATA_DIR_NAME: &str = "data";
 }
 
 /// 窗口状态限制
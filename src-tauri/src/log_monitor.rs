@@ -0,0 +1,522 @@
+// Antigravity 日志监控模块
+// 跟踪 Antigravity 自身的日志文件，识别配额耗尽、限流和认证失败错误，
+// 将其归属到当前登录的账户并记录冷却时间，按设置提示或自动切换到可用账户
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use walkdir::WalkDir;
+
+use crate::account_registry::{self, RotationDirection};
+use crate::platform_utils;
+
+/// 账户异常事件
+pub const ACCOUNT_ISSUE_EVENT: &str = "account-health://issue";
+
+/// 日志轮询间隔
+const POLL_INTERVAL_SECS: u64 = 5;
+
+/// 单次读取的最大字节数，避免日志突然暴涨时占用过多内存
+const MAX_READ_BYTES: u64 = 1024 * 1024;
+
+/// 无法归属到已保存账户的问题，最短提示间隔
+const UNKNOWN_ACCOUNT_THROTTLE: Duration = Duration::from_secs(60);
+
+/// 上一次处理无法归属账户的问题的时间
+static LAST_UNKNOWN_ISSUE: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+
+/// 配额耗尽
+static QUOTA_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(RESOURCE_EXHAUSTED|quota (exceeded|exhausted)|exhausted your .*quota|out of credits)")
+        .expect("无效的配额匹配规则")
+});
+
+/// 限流状态码
+///
+/// 状态码只在 HTTP 状态上下文中匹配（如 `status: 429`、`"code": 429`、`HTTP/1.1 429`），
+/// 避免误匹配日志时间戳的毫秒、端口号或行号
+static RATE_LIMIT_STATUS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)(status|code|http(/\d(\.\d)?)?)"?\s*[:=]?\s*"?429\b"#)
+        .expect("无效的限流匹配规则")
+});
+
+/// 限流关键字（不匹配 `RateLimiter` 这类组件名），只在错误上下文中生效
+static RATE_LIMIT_KEYWORDS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(rate[ _-]?limit(ed|[^a-z]|$)|too many requests)").expect("无效的限流匹配规则")
+});
+
+/// 认证失败状态码（同样只在 HTTP 状态上下文中匹配）和 gRPC 状态
+static AUTH_STATUS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)((status|code|http(/\d(\.\d)?)?)"?\s*[:=]?\s*"?401\b|UNAUTHENTICATED)"#)
+        .expect("无效的认证匹配规则")
+});
+
+/// 认证失败关键字，只在错误上下文中生效（令牌正常刷新时也会输出 token expired）
+static AUTH_KEYWORDS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(invalid_grant|token (has )?expired|invalid authentication credentials)")
+        .expect("无效的认证匹配规则")
+});
+
+/// 错误上下文：错误或警告级别的日志行
+static ERROR_CONTEXT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\[(error|warn|warning)\]").expect("无效的日志级别匹配规则"));
+
+/// 日志行开头的时间戳格式（VS Code 系列：`2025-11-20 10:15:30.429`）
+const LOG_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// 解析日志行开头的时间戳（本地时间），没有时间戳时返回 None
+fn line_timestamp(line: &str) -> Option<chrono::DateTime<chrono::Local>> {
+    use chrono::TimeZone;
+
+    let prefix = line.get(..23)?;
+    let naive = chrono::NaiveDateTime::parse_from_str(prefix, LOG_TIMESTAMP_FORMAT).ok()?;
+    chrono::Local.from_local_datetime(&naive).earliest()
+}
+
+/// 日志行是否早于该账户最近一次切换（属于切换前的账户，不能归属到当前账户）
+fn logged_before_switch(entry: &account_registry::AccountEntry, line: &str) -> bool {
+    let Some(logged_at) = line_timestamp(line) else {
+        return false;
+    };
+    entry
+        .last_switched
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .is_some_and(|switched_at| logged_at < switched_at)
+}
+
+/// 日志中识别出的问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    Quota,
+    RateLimit,
+    Auth,
+}
+
+/// 各类问题的冷却时长（分钟，保存在设置中）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IssueCooldowns {
+    pub quota_minutes: u32,
+    pub rate_limit_minutes: u32,
+    pub auth_minutes: u32,
+}
+
+impl Default for IssueCooldowns {
+    fn default() -> Self {
+        Self {
+            quota_minutes: 60,
+            rate_limit_minutes: 5,
+            auth_minutes: 30,
+        }
+    }
+}
+
+impl IssueCooldowns {
+    /// 冷却时长不能超过 7 天
+    pub fn validate(&self) -> Result<(), String> {
+        const MAX_MINUTES: u32 = 7 * 24 * 60;
+        if [
+            self.quota_minutes,
+            self.rate_limit_minutes,
+            self.auth_minutes,
+        ]
+        .iter()
+        .any(|m| *m > MAX_MINUTES)
+        {
+            return Err(format!("冷却时长不能超过 {} 分钟", MAX_MINUTES));
+        }
+        Ok(())
+    }
+}
+
+impl IssueKind {
+    /// 各类问题的冷却时长
+    fn cooldown(self, cooldowns: &IssueCooldowns) -> chrono::Duration {
+        let minutes = match self {
+            IssueKind::Quota => cooldowns.quota_minutes,
+            IssueKind::RateLimit => cooldowns.rate_limit_minutes,
+            IssueKind::Auth => cooldowns.auth_minutes,
+        };
+        chrono::Duration::minutes(minutes as i64)
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            IssueKind::Quota => "配额耗尽",
+            IssueKind::RateLimit => "请求限流",
+            IssueKind::Auth => "认证失败",
+        }
+    }
+
+    /// 识别日志行中的问题，优先级：配额 > 认证 > 限流
+    ///
+    /// 状态码可以单独判定；关键字只有出现在错误或警告级别的日志行中才算，
+    /// 避免把组件初始化、令牌刷新等正常日志当成问题而触发冷却和自动切换
+    fn detect(line: &str) -> Option<Self> {
+        let error_context = ERROR_CONTEXT.is_match(line);
+        if QUOTA_PATTERN.is_match(line) {
+            Some(IssueKind::Quota)
+        } else if AUTH_STATUS.is_match(line) || (error_context && AUTH_KEYWORDS.is_match(line)) {
+            Some(IssueKind::Auth)
+        } else if RATE_LIMIT_STATUS.is_match(line)
+            || (error_context && RATE_LIMIT_KEYWORDS.is_match(line))
+        {
+            Some(IssueKind::RateLimit)
+        } else {
+            None
+        }
+    }
+}
+
+/// 检测到问题后的处理方式（保存在设置中）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaAction {
+    /// 只记录冷却，不提示
+    Off,
+    /// 发送事件，建议切换到可用账户
+    #[default]
+    Suggest,
+    /// 自动切换到轮换顺序中的下一个可用账户
+    Switch,
+}
+
+/// 账户异常事件载荷
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountIssue {
+    pub kind: IssueKind,
+    pub account_id: Option<String>,
+    pub email: Option<String>,
+    pub cooldown_until: Option<String>,
+    /// 触发的日志行（截断）
+    pub log_line: String,
+    pub log_file: String,
+    /// 建议切换到的可用账户
+    pub suggested_account: Option<account_registry::AccountEntry>,
+    /// 是否已自动发起切换
+    pub auto_switched: bool,
+}
+
+/// 获取 Antigravity 的日志目录（与 globalStorage 同属用户数据目录）
+pub fn get_antigravity_logs_dir() -> Option<PathBuf> {
+    // globalStorage -> User -> 用户数据目录
+    platform_utils::get_antigravity_data_dir()
+        .and_then(|dir| dir.parent()?.parent().map(|root| root.join("logs")))
+}
+
+/// 获取最新一次启动的日志目录（按目录名排序，VS Code 系列以时间戳命名）
+fn latest_session_dir(logs_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(logs_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .max()
+}
+
+/// 日志跟踪状态
+#[derive(Default)]
+struct LogTailer {
+    session_dir: Option<PathBuf>,
+    offsets: HashMap<PathBuf, u64>,
+}
+
+impl LogTailer {
+    /// 读取所有日志文件的新增行
+    ///
+    /// 首次发现的文件从末尾开始跟踪，不会重复处理历史错误；新启动会话中的文件从头读取
+    fn poll(&mut self, logs_dir: &Path) -> Vec<(PathBuf, String)> {
+        let Some(session_dir) = latest_session_dir(logs_dir) else {
+            return Vec::new();
+        };
+
+        let first_scan = self.session_dir.is_none();
+        if self.session_dir.as_ref() != Some(&session_dir) {
+            self.offsets.clear();
+            self.session_dir = Some(session_dir.clone());
+        }
+
+        let mut lines = Vec::new();
+        for entry in WalkDir::new(&session_dir).into_iter().flatten() {
+            let path = entry.path();
+            if !entry.file_type().is_file() || path.extension().is_none_or(|ext| ext != "log") {
+                continue;
+            }
+
+            let len = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            let offset = *self
+                .offsets
+                .entry(path.to_path_buf())
+                .or_insert(if first_scan { len } else { 0 });
+            // 文件被截断时从头读取
+            let offset = if len < offset { 0 } else { offset };
+            if len == offset {
+                continue;
+            }
+
+            match read_new_lines(path, offset, len) {
+                Ok((new_offset, new_lines)) => {
+                    self.offsets.insert(path.to_path_buf(), new_offset);
+                    lines.extend(new_lines.into_iter().map(|l| (path.to_path_buf(), l)));
+                }
+                Err(e) => log::warn!("⚠️ 读取日志 {} 失败: {}", path.display(), e),
+            }
+        }
+
+        lines
+    }
+}
+
+/// 读取 `offset` 之后的完整行，返回新的偏移量（不包含末尾未写完的行）
+fn read_new_lines(path: &Path, offset: u64, len: u64) -> Result<(u64, Vec<String>), String> {
+    let start = offset.max(len.saturating_sub(MAX_READ_BYTES));
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    file.seek(SeekFrom::Start(start))
+        .map_err(|e| e.to_string())?;
+
+    let mut buffer = Vec::new();
+    file.take(len - start)
+        .read_to_end(&mut buffer)
+        .map_err(|e| e.to_string())?;
+
+    let Some(last_newline) = buffer.iter().rposition(|b| *b == b'\n') else {
+        return Ok((start, Vec::new()));
+    };
+
+    let lines = String::from_utf8_lossy(&buffer[..last_newline])
+        .lines()
+        .map(|l| l.to_string())
+        .collect();
+    Ok((start + last_newline as u64 + 1, lines))
+}
+
+/// 处理识别出的问题：记录冷却并按设置提示或切换
+///
+/// 切换或重启过程中读到的日志无法确定属于哪个账户，直接忽略
+async fn handle_issue(app: &AppHandle, kind: IssueKind, log_file: &Path, line: &str) {
+    if crate::account_switcher::is_operation_running() {
+        log::info!("⏭️ 账户操作进行中，忽略{}日志: {}", kind.label(), line);
+        return;
+    }
+
+    let email = crate::antigravity_backup::read_current_email()
+        .ok()
+        .flatten();
+    let registry = account_registry::load_registry().ok();
+    let entry = email.as_deref().and_then(|email| {
+        registry
+            .as_ref()
+            .and_then(|r| r.find_by_email(email))
+            .cloned()
+    });
+
+    // 同一账户已处于冷却中或日志早于切换到该账户时不处理；无法归属账户时按时间节流
+    match &entry {
+        Some(entry) if entry.in_cooldown() => return,
+        Some(entry) if logged_before_switch(entry, line) => {
+            log::info!("⏭️ 日志早于切换到 {} 的时间，忽略: {}", entry.email, line);
+            return;
+        }
+        Some(_) => {}
+        None => {
+            let mut last = LAST_UNKNOWN_ISSUE.lock().unwrap_or_else(|e| e.into_inner());
+            if last.is_some_and(|t| t.elapsed() < UNKNOWN_ACCOUNT_THROTTLE) {
+                return;
+            }
+            *last = Some(Instant::now());
+        }
+    }
+
+    log::warn!(
+        "🚫 检测到{} ({}): {}",
+        kind.label(),
+        email.as_deref().unwrap_or("未知账户"),
+        line
+    );
+
+    let settings = crate::settings::get();
+    let mut cooldown_until = None;
+    if let Some(entry) = &entry {
+        let until = chrono::Local::now() + kind.cooldown(&settings.cooldowns);
+        match account_registry::update_registry(|registry, _| {
+            registry.set_cooldown(&entry.id, Some(until), Some(kind.label().to_string()))
+        }) {
+            Ok(_) => cooldown_until = Some(until.to_rfc3339()),
+            Err(e) => log::warn!("⚠️ 记录账户冷却失败: {}", e),
+        }
+    }
+
+    // 无法确定当前登录的账户时不建议也不自动切换：冷却无法归属，切换还会丢弃未保存的账户
+    let action = settings.quota_action;
+    let suggested_account = entry.as_ref().and_then(|_| {
+        account_registry::load_registry()
+            .ok()
+            .and_then(|r| r.rotation_target(RotationDirection::Next).ok())
+    });
+
+    let auto_switched = action == QuotaAction::Switch
+        && suggested_account.is_some()
+        && !crate::account_switcher::is_operation_running();
+
+    let issue = AccountIssue {
+        kind,
+        account_id: entry.as_ref().map(|e| e.id.clone()),
+        email,
+        cooldown_until,
+        log_line: line.chars().take(500).collect(),
+        log_file: log_file.to_string_lossy().to_string(),
+        suggested_account,
+        auto_switched,
+    };
+
+    if action != QuotaAction::Off {
+        if let Err(e) = app.emit(ACCOUNT_ISSUE_EVENT, &issue) {
+            log::warn!("⚠️ 发送账户异常事件失败: {}", e);
+        }
+    }
+
+    if auto_switched {
+        log::info!("🔁 {}，自动切换到下一个可用账户", kind.label());
+        match crate::account_switcher::switch_in_rotation(Some(app), RotationDirection::Next).await
+        {
+            Ok(report) => log::info!("🔁 自动切换结束: {}", report.summary),
+            Err(e) => log::warn!("⚠️ 自动切换失败: {}", e),
+        }
+    }
+}
+
+/// 启动日志监控
+pub fn start_log_monitor(app: AppHandle) {
+    let Some(logs_dir) = get_antigravity_logs_dir() else {
+        log::warn!("⚠️ 未找到 Antigravity 日志目录，跳过日志监控");
+        return;
+    };
+
+    tauri::async_runtime::spawn(async move {
+        println!("📜 开始监控 Antigravity 日志: {}", logs_dir.display());
+        let mut tailer = LogTailer::default();
+
+        loop {
+            if logs_dir.exists() {
+                for (file, line) in tailer.poll(&logs_dir) {
+                    if let Some(kind) = IssueKind::detect(&line) {
+                        handle_issue(&app, kind, &file, &line).await;
+                    }
+                }
+            }
+
+            tokio::time::sleep(tokio::time::Duration::from_secs(POLL_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_ignores_timestamp_milliseconds() {
+        assert_eq!(
+            IssueKind::detect("2025-11-20 10:15:30.429 [info] Extension host started"),
+            None
+        );
+        assert_eq!(
+            IssueKind::detect("2025-11-20 10:15:30.401 [info] Listening on port 401"),
+            None
+        );
+    }
+
+    #[test]
+    fn detect_status_codes_in_http_context() {
+        assert_eq!(
+            IssueKind::detect("2025-11-20 10:15:30.123 [error] request failed: status: 429"),
+            Some(IssueKind::RateLimit)
+        );
+        assert_eq!(
+            IssueKind::detect(r#"[error] {"error":{"code": 401,"message":"bad token"}}"#),
+            Some(IssueKind::Auth)
+        );
+        assert_eq!(
+            IssueKind::detect("HTTP/1.1 429 Too Many Requests"),
+            Some(IssueKind::RateLimit)
+        );
+    }
+
+    #[test]
+    fn detect_keyword_priority() {
+        assert_eq!(
+            IssueKind::detect("status 429 RESOURCE_EXHAUSTED: quota exceeded"),
+            Some(IssueKind::Quota)
+        );
+        assert_eq!(
+            IssueKind::detect("[error] rate limit reached, token has expired"),
+            Some(IssueKind::Auth)
+        );
+        assert_eq!(
+            IssueKind::detect("[warning] Rate-limit hit, retrying"),
+            Some(IssueKind::RateLimit)
+        );
+        assert_eq!(
+            IssueKind::detect("[error] RATE_LIMIT_EXCEEDED"),
+            Some(IssueKind::RateLimit)
+        );
+    }
+
+    #[test]
+    fn detect_ignores_routine_lines() {
+        assert_eq!(
+            IssueKind::detect("2025-11-20 10:15:30.123 [info] RateLimiter initialized"),
+            None
+        );
+        assert_eq!(
+            IssueKind::detect("2025-11-20 10:15:30.123 [error] RateLimiter initialized"),
+            None
+        );
+        assert_eq!(
+            IssueKind::detect("2025-11-20 10:15:30.123 [info] rate limit set to 60 requests/min"),
+            None
+        );
+        assert_eq!(
+            IssueKind::detect(
+                "2025-11-20 10:15:30.123 [info] Access token expired, refreshing token"
+            ),
+            None
+        );
+        assert_eq!(
+            IssueKind::detect("2025-11-20 10:15:30.123 [info] token has expired, refreshed"),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_line_timestamp() {
+        let time = line_timestamp("2025-11-20 10:15:30.429 [error] status: 429").unwrap();
+        assert_eq!(time.naive_local().to_string(), "2025-11-20 10:15:30.429");
+        assert!(line_timestamp("[error] status: 429").is_none());
+    }
+
+    #[test]
+    fn lines_before_switch_are_not_attributed() {
+        let mut entry = crate::test_utils::entry("id", "a@example.com");
+        entry.last_switched = Some("2025-11-20T10:15:30+00:00".to_string());
+
+        assert!(logged_before_switch(
+            &entry,
+            "2000-01-01 00:00:00.000 [error] status: 429"
+        ));
+        assert!(!logged_before_switch(
+            &entry,
+            "2099-01-01 00:00:00.000 [error] status: 429"
+        ));
+        assert!(!logged_before_switch(&entry, "[error] status: 429"));
+    }
+}
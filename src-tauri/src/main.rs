@@ -3,11 +3,10 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
 
 use log::LevelFilter;
 use rusqlite::Connection;
+use tauri::Manager;
 
 /// Antigravity 清理模块
 mod antigravity_cleanup;
@@ -21,6 +20,42 @@ mod antigravity_restore;
 /// Antigravity 启动模块
 mod antigravity_starter;
 
+/// Antigravity 进程状态模块
+mod antigravity_process;
+
+/// Antigravity 登录状态监听模块
+mod auth_watcher;
+
+/// 账户备份保鲜模块
+mod backup_freshness;
+
+/// 定时任务模块
+mod scheduler;
+
+/// Antigravity 日志监控模块
+mod log_monitor;
+
+/// 桌面通知模块
+mod notifications;
+
+/// 应用设置模块
+mod settings;
+
+/// 开机自启模块
+mod autostart;
+
+/// 账户注册表模块
+mod account_registry;
+
+/// 账户头像模块
+mod account_avatar;
+
+/// 账户切换模块
+mod account_switcher;
+
+/// 账户使用时长统计模块
+mod account_activity;
+
 /// 窗口状态管理模块
 mod window_state_manager;
 
@@ -42,38 +77,65 @@ mod config_manager;
 /// 工具模块
 mod utils;
 
+/// 单元测试辅助模块
+#[cfg(test)]
+mod test_utils;
+
 /// 命令模块
 mod commands;
 
 // 重新导出命令函数以保持 invoke_handler 兼容性
 use crate::commands::{
+    add_antigravity_account_alias,
+    add_scheduled_job,
     backup_and_restart_antigravity,
     backup_antigravity_current_account,
     backup_profile,
+    cancel_account_switch,
+    clear_account_cooldown,
     clear_all_antigravity_data,
     clear_all_backups,
     clear_logs,
     collect_backup_contents,
     delete_backup,
+    disable_autostart,
     disable_system_tray,
+    enable_autostart,
     // tray_commands
     enable_system_tray,
     // 日志导出命令
     export_logs,
     find_antigravity_installations,
+    find_duplicate_antigravity_accounts,
+    get_account_rotation,
+    get_account_usage_summary,
+    get_antigravity_account_avatar,
+    get_antigravity_account_avatars,
     get_antigravity_accounts,
+    get_antigravity_processes,
+    get_backup_refresh_policy,
     get_current_antigravity_info,
     get_log_content,
     get_log_info,
+    get_notification_settings,
     // platform_commands
     get_platform_info,
+    get_quota_action,
+    get_settings,
+    get_switch_options,
     get_system_tray_state,
     is_antigravity_running,
+    is_autostart_enabled,
     is_system_tray_enabled,
     // process_commands
     kill_antigravity,
     list_backups,
+    list_scheduled_jobs,
+    merge_antigravity_accounts,
     minimize_to_tray,
+    remove_antigravity_account_alias,
+    remove_scheduled_job,
+    rename_antigravity_account,
     resolve_antigravity_path,
     // 最后2个有依赖的函数
     restore_antigravity_account,
@@ -82,10 +144,22 @@ use crate::commands::{
     restore_profile,
     save_antigravity_path,
     save_system_tray_state,
+    set_account_flags,
+    set_account_rotation,
+    set_backup_refresh_policy,
+    set_notification_settings,
+    set_quota_action,
+    set_start_hidden,
     start_antigravity,
     // account_commands (前5个零依赖函数)
     switch_antigravity_account,
     switch_to_antigravity_account,
+    switch_to_last_account,
+    switch_to_next_account,
+    switch_to_previous_account,
+    update_scheduled_job,
+    update_settings,
+    update_switch_options,
     validate_antigravity_path,
 };
 
@@ -113,55 +187,13 @@ struct AntigravityAccount {
 
 // 导入系统托盘管理器
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 struct AppState {
     profiles: HashMap<String, ProfileInfo>,
-    config_dir: PathBuf,
     antigravity_accounts: HashMap<String, AntigravityAccount>,
     current_account_id: Option<String>,
 }
 
-impl Default for AppState {
-    fn default() -> Self {
-        // 智能检测配置目录，确保跨平台兼容性
-        let config_dir = if cfg!(windows) {
-            // Windows: 优先使用 APPDATA 环境变量
-            std::env::var_os("APPDATA")
-                .map(|appdata| PathBuf::from(appdata).join(".antigravity-agent"))
-                .or_else(|| {
-                    // 备用方案：通过用户主目录构建 AppData\Roaming 路径
-                    dirs::home_dir().map(|home| {
-                        home.join("AppData")
-                            .join("Roaming")
-                            .join(".antigravity-agent")
-                    })
-                })
-                .or_else(|| {
-                    // 最后备用：使用系统标准配置目录
-                    dirs::config_dir().map(|config| config.join(".antigravity-agent"))
-                })
-                .unwrap_or_else(|| PathBuf::from(".antigravity-agent"))
-        } else {
-            // macOS/Linux: 使用标准配置目录
-            dirs::config_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join(".antigravity-agent")
-        };
-
-        // 确保配置目录存在
-        fs::create_dir_all(&config_dir)
-            .map_err(|e| eprintln!("警告：无法创建配置目录 {:?}: {}", config_dir, e))
-            .ok();
-
-        Self {
-            profiles: HashMap::new(),
-            config_dir,
-            antigravity_accounts: HashMap::new(),
-            current_account_id: None,
-        }
-    }
-}
-
 fn main() {
     println!("🚀 启动 Antigravity Agent");
 
@@ -174,28 +206,64 @@ fn main() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(autostart::plugin())
         .manage(AppState::default())
         .setup(|app| {
+            // 统一的配置目录，首次启动新版本时把旧位置的数据迁移过来（需在读取任何配置之前）
+            let config_manager = config_manager::ConfigManager::new()?;
+            let migration_notes = config_manager.migrate_legacy_locations();
+
             // 初始化简单日志记录器
-            let log_dir = dirs::config_dir()
-                .unwrap_or_else(|| std::path::PathBuf::from("."))
-                .join("antigravity-agent")
-                .join("logs");
-            fs::create_dir_all(&log_dir).ok();
-
-            simple_logging::log_to_file(log_dir.join("antigravity-agent.log"), LevelFilter::Info)
-                .ok();
+            simple_logging::log_to_file(config_manager.log_file()?, LevelFilter::Info).ok();
+            log::info!(
+                "📁 数据目录: {} ({:?})",
+                config_manager.config_dir().display(),
+                config_manager.data_dir_source()
+            );
+            for note in migration_notes {
+                log::info!("📦 {}", note);
+            }
+            // 加载应用设置（执行旧版配置迁移）
+            settings::init(app.handle().clone());
+            // 记录应用句柄，用于发送注册表变化事件
+            account_registry::init(app.handle().clone());
+
             // 初始化窗口事件处理器
             if let Err(e) = window_event_handler::init_window_event_handler(app) {
                 eprintln!("⚠️  窗口事件处理器初始化失败: {}", e);
             }
 
+            // 启动账户使用时长监控
+            account_activity::start_process_monitor();
+
+            // 监听 Antigravity 登录状态变化
+            auth_watcher::start_auth_watcher(app.handle().clone());
+
+            // 启动定时任务调度器
+            scheduler::start_scheduler(app.handle().clone());
+
+            // 监控 Antigravity 日志中的配额、限流和认证错误
+            log_monitor::start_log_monitor(app.handle().clone());
+
             // 初始化系统托盘管理器
             match system_tray::SystemTrayManager::initialize_global(app.handle()) {
                 Ok(_) => println!("✅ 系统托盘管理器初始化成功"),
                 Err(e) => println!("⚠️ 系统托盘管理器初始化失败: {}", e),
             }
 
+            // 让系统中的开机自启项与设置保持一致
+            autostart::sync_with_settings(app.handle());
+
+            // 主窗口默认不可见，开机自启且设置了隐藏启动时留在托盘中，否则显示窗口
+            if autostart::should_start_hidden() {
+                println!("🙈 开机自启，窗口保持隐藏在系统托盘中");
+            } else if let Some(window) = app.get_webview_window("main") {
+                if let Err(e) = window.show() {
+                    eprintln!("⚠️ 显示主窗口失败: {}", e);
+                }
+            }
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -213,7 +281,48 @@ fn main() {
             backup_antigravity_current_account,
             restore_antigravity_account,
             switch_to_antigravity_account,
+            cancel_account_switch,
+            get_switch_options,
+            update_switch_options,
+            get_backup_refresh_policy,
+            set_backup_refresh_policy,
+            // 账户轮换命令
+            switch_to_next_account,
+            switch_to_previous_account,
+            switch_to_last_account,
+            get_account_rotation,
+            set_account_rotation,
+            set_account_flags,
+            clear_account_cooldown,
+            get_quota_action,
+            set_quota_action,
+            // 定时任务命令
+            list_scheduled_jobs,
+            add_scheduled_job,
+            update_scheduled_job,
+            remove_scheduled_job,
+            // 应用设置命令
+            get_settings,
+            update_settings,
+            // 开机自启命令
+            enable_autostart,
+            disable_autostart,
+            is_autostart_enabled,
+            set_start_hidden,
+            // 系统通知命令
+            get_notification_settings,
+            set_notification_settings,
             clear_all_antigravity_data,
+            // 账户注册表命令
+            rename_antigravity_account,
+            add_antigravity_account_alias,
+            remove_antigravity_account_alias,
+            find_duplicate_antigravity_accounts,
+            merge_antigravity_accounts,
+            get_antigravity_account_avatars,
+            get_antigravity_account_avatar,
+            // 账户使用统计命令
+            get_account_usage_summary,
             // 进程管理命令
             kill_antigravity,
             start_antigravity,
@@ -225,6 +334,7 @@ fn main() {
             resolve_antigravity_path,
             save_antigravity_path,
             is_antigravity_running,
+            get_antigravity_processes,
             enable_system_tray,
             disable_system_tray,
             minimize_to_tray,
@@ -237,6 +347,12 @@ fn main() {
             get_log_info,
             clear_logs
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // 退出前立即保存窗口状态，避免丢失尚未执行的延迟保存
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                window_event_handler::flush_window_state(app_handle);
+            }
+        });
 }
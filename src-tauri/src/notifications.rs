@@ -0,0 +1,86 @@
+// 桌面通知模块
+// 账户切换、备份、恢复、登出和定时任务完成或失败时发送系统通知，
+// 窗口隐藏在托盘中时也能看到操作结果；每类通知可以单独开关
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+/// 通知正文的最大字符数，过长的步骤汇总会被截断
+const MAX_BODY_CHARS: usize = 200;
+
+/// 需要发送通知的操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    Switch,
+    Backup,
+    Restore,
+    Logout,
+    ScheduledJob,
+}
+
+impl NotificationEvent {
+    fn label(self) -> &'static str {
+        match self {
+            NotificationEvent::Switch => "账户切换",
+            NotificationEvent::Backup => "账户备份",
+            NotificationEvent::Restore => "账户恢复",
+            NotificationEvent::Logout => "登出",
+            NotificationEvent::ScheduledJob => "定时任务",
+        }
+    }
+}
+
+/// 各类通知的开关（保存在设置中），默认全部开启
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationSettings {
+    pub switch: bool,
+    pub backup: bool,
+    pub restore: bool,
+    pub logout: bool,
+    pub scheduled_job: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            switch: true,
+            backup: true,
+            restore: true,
+            logout: true,
+            scheduled_job: true,
+        }
+    }
+}
+
+impl NotificationSettings {
+    /// 某类通知是否开启
+    pub fn is_enabled(&self, event: NotificationEvent) -> bool {
+        match event {
+            NotificationEvent::Switch => self.switch,
+            NotificationEvent::Backup => self.backup,
+            NotificationEvent::Restore => self.restore,
+            NotificationEvent::Logout => self.logout,
+            NotificationEvent::ScheduledJob => self.scheduled_job,
+        }
+    }
+}
+
+/// 发送操作结果通知，对应类型的通知被关闭时直接忽略
+pub fn notify_result(app: &AppHandle, event: NotificationEvent, result: &Result<String, String>) {
+    if !crate::settings::get().notifications.is_enabled(event) {
+        return;
+    }
+
+    let (title, message) = match result {
+        Ok(message) => (format!("{}完成", event.label()), message),
+        Err(e) => (format!("{}失败", event.label()), e),
+    };
+    let body: String = message.chars().take(MAX_BODY_CHARS).collect();
+
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        log::warn!("⚠️ 发送系统通知失败: {}", e);
+    }
+}
@@ -1,11 +1,4 @@
-use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
-
-use serde::{Deserialize, Serialize};
-use sysinfo::System;
-
-use crate::constants::paths;
 
 /// 获取Antigravity应用数据目录（跨平台）
 pub fn get_antigravity_data_dir() -> Option<PathBuf> {
@@ -38,73 +31,29 @@ pub fn get_antigravity_data_dir() -> Option<PathBuf> {
     }
 }
 
-fn ensure_config_dir() -> Result<PathBuf, String> {
-    let config_dir = dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(paths::CONFIG_DIR_NAME);
-
-    fs::create_dir_all(&config_dir).map_err(|e| format!("创建配置目录失败: {e}"))?;
-
-    Ok(config_dir)
-}
-
-fn config_file_path() -> Result<PathBuf, String> {
-    Ok(ensure_config_dir()?.join("config.json"))
-}
-
-#[derive(Debug, Serialize, Deserialize, Default)]
-struct AgentConfig {
-    #[serde(rename = "antigravityPath")]
-    antigravity_path: Option<String>,
-}
-
-fn load_agent_config() -> Result<AgentConfig, String> {
-    let path = config_file_path()?;
-    if !path.exists() {
-        return Ok(AgentConfig::default());
-    }
-
-    let content = fs::read_to_string(&path).map_err(|e| format!("读取配置失败: {e}"))?;
-
-    serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {e}"))
-}
-
-fn save_agent_config(config: &AgentConfig) -> Result<(), String> {
-    let path = config_file_path()?;
-    let content =
-        serde_json::to_string_pretty(config).map_err(|e| format!("序列化配置失败: {e}"))?;
-    fs::write(&path, content).map_err(|e| format!("写入配置失败: {e}"))
-}
-
 fn validate_antigravity_exe(path: &Path) -> bool {
     path.is_file()
 }
 
-fn load_persisted_antigravity_path() -> Option<PathBuf> {
-    if !cfg!(windows) {
-        return None;
-    }
-
-    load_agent_config().ok().and_then(|cfg| {
-        cfg.antigravity_path
-            .as_deref()
-            .map(PathBuf::from)
-            .filter(|p| validate_antigravity_exe(p))
-    })
+/// 读取设置中保存的 Antigravity 可执行文件路径（文件已不存在时忽略）
+pub fn load_persisted_antigravity_path() -> Option<PathBuf> {
+    crate::settings::get()
+        .antigravity_path
+        .map(PathBuf::from)
+        .filter(|p| validate_antigravity_exe(p))
 }
 
+/// 把 Antigravity 可执行文件路径保存到设置中
 pub fn persist_antigravity_path(path: &Path) -> Result<(), String> {
-    if !cfg!(windows) {
-        return Ok(());
-    }
-
     if !validate_antigravity_exe(path) {
         return Err("无效的 Antigravity 可执行文件路径".to_string());
     }
 
-    let mut config = load_agent_config().unwrap_or_default();
-    config.antigravity_path = Some(path.to_string_lossy().to_string());
-    save_agent_config(&config)
+    let path = path.to_string_lossy().to_string();
+    if crate::settings::get().antigravity_path.as_deref() == Some(path.as_str()) {
+        return Ok(());
+    }
+    crate::settings::update(|settings| settings.antigravity_path = Some(path)).map(|_| ())
 }
 
 /// 获取Antigravity状态数据库文件路径
@@ -165,28 +114,15 @@ fn get_antigravity_windows_paths() -> Vec<PathBuf> {
     antigravity_paths
 }
 
+/// 获取运行中 Antigravity 主进程的可执行文件路径（不含辅助进程）
 pub fn find_running_antigravity_exes() -> Vec<PathBuf> {
-    if !cfg!(windows) {
-        return Vec::new();
-    }
-
-    let mut system = System::new();
-    system.refresh_processes();
-
-    let mut paths = Vec::new();
-    for process in system.processes_by_name("Antigravity.exe") {
-        if let Some(exe) = process.exe() {
-            let path = exe.to_path_buf();
-            if validate_antigravity_exe(&path) {
-                paths.push(path);
-            }
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for process in crate::antigravity_process::find_antigravity_processes() {
+        if process.is_helper {
+            continue;
         }
-    }
-
-    for process in system.processes_by_name("Antigravity") {
-        if let Some(exe) = process.exe() {
-            let path = exe.to_path_buf();
-            if validate_antigravity_exe(&path) {
+        if let Some(path) = process.exe.map(PathBuf::from) {
+            if validate_antigravity_exe(&path) && !paths.contains(&path) {
                 paths.push(path);
             }
         }
@@ -219,12 +155,9 @@ pub fn resolve_antigravity_exe_windows() -> Option<PathBuf> {
     None
 }
 
+/// 检查是否有 Antigravity 进程正在运行（包括辅助进程）
 pub fn is_antigravity_process_running() -> bool {
-    if !cfg!(windows) {
-        return false;
-    }
-
-    !find_running_antigravity_exes().is_empty()
+    !crate::antigravity_process::find_antigravity_processes().is_empty()
 }
 
 /// 获取所有可能的Antigravity数据库路径
@@ -254,58 +187,3 @@ pub fn get_all_antigravity_db_paths() -> Vec<PathBuf> {
 
     db_paths
 }
-
-/// 关闭Antigravity进程
-pub fn kill_antigravity_processes() -> Result<String, String> {
-    match std::env::consts::OS {
-        "windows" => {
-            // Windows: 尝试多种可能的进程名
-            let process_names = vec!["Antigravity.exe", "Antigravity"];
-            let mut last_error = String::new();
-
-            for process_name in process_names {
-                let output = Command::new("taskkill")
-                    .args(["/F", "/IM", process_name])
-                    .output()
-                    .map_err(|e| format!("执行taskkill命令失败: {}", e))?;
-
-                if output.status.success() {
-                    return Ok(format!("已成功关闭Antigravity进程 ({})", process_name));
-                } else {
-                    last_error = format!(
-                        "关闭进程 {} 失败: {:?}",
-                        process_name,
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                }
-            }
-
-            Err(last_error)
-        }
-        "macos" | "linux" => {
-            // macOS/Linux: 使用pkill命令，尝试多种进程名模式
-            let process_patterns = vec!["Antigravity", "antigravity"];
-            let mut last_error = String::new();
-
-            for pattern in process_patterns {
-                let output = Command::new("pkill")
-                    .args(["-f", pattern])
-                    .output()
-                    .map_err(|e| format!("执行pkill命令失败: {}", e))?;
-
-                if output.status.success() {
-                    return Ok(format!("已成功关闭Antigravity进程 (模式: {})", pattern));
-                } else {
-                    last_error = format!(
-                        "关闭进程失败 (模式: {}): {:?}",
-                        pattern,
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                }
-            }
-
-            Err(last_error)
-        }
-        _ => Err("不支持的操作系统".to_string()),
-    }
-}
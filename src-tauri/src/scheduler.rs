@@ -0,0 +1,482 @@
+// 定时任务模块
+// 持久化保存定时切换账户、定时备份等任务，由后台调度器按时执行
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, TimeZone};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use crate::account_registry::{self, RotationDirection};
+use crate::account_switcher::{self, OperationKind};
+use crate::config_manager::ConfigManager;
+
+/// 任务执行完成事件
+pub const JOB_FINISHED_EVENT: &str = "scheduler://job-finished";
+
+/// 调度器检查间隔
+const TICK_INTERVAL_SECS: u64 = 30;
+
+/// 每日任务错过计划时间后仍允许补执行的时长（例如电脑刚从睡眠中唤醒）
+const DAILY_GRACE_MINUTES: i64 = 10;
+
+/// 任务文件读写锁
+static SCHEDULE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// 任务要执行的操作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobAction {
+    /// 切换到指定账户（账户 ID / 邮箱 / 别名 / 文件名）
+    SwitchAccount { account: String },
+    /// 切换到轮换顺序中的下一个账户
+    SwitchNext,
+    /// 备份当前登录的账户
+    BackupCurrent,
+}
+
+/// 任务触发方式
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobTrigger {
+    /// 每天的固定时间（HH:MM），`weekdays` 为 1（周一）到 7（周日），为空表示每天
+    Daily {
+        time: String,
+        #[serde(default)]
+        weekdays: Vec<u32>,
+    },
+    /// 每隔固定分钟数
+    Interval { minutes: u32 },
+}
+
+/// 定时任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub enabled: bool,
+    pub action: JobAction,
+    pub trigger: JobTrigger,
+    pub created_at: String,
+    #[serde(default)]
+    pub last_run: Option<String>,
+    #[serde(default)]
+    pub last_result: Option<String>,
+    #[serde(default)]
+    pub last_success: Option<bool>,
+}
+
+/// 任务执行结果事件载荷
+#[derive(Debug, Clone, Serialize)]
+pub struct JobFinished {
+    pub job_id: String,
+    pub name: Option<String>,
+    pub success: bool,
+    pub message: String,
+}
+
+/// 读取任务文件，文件不存在时返回空列表
+fn read_jobs(path: &Path) -> Result<Vec<ScheduledJob>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("读取定时任务失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析定时任务失败: {}", e))
+}
+
+/// 只读方式加载任务文件
+fn load_jobs() -> Result<Vec<ScheduledJob>, String> {
+    let _guard = SCHEDULE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = ConfigManager::new()?.schedule_file();
+    read_jobs(&path)
+}
+
+/// 在持有锁的情况下读取、修改并写回任务文件
+fn with_jobs<T>(f: impl FnOnce(&mut Vec<ScheduledJob>) -> Result<T, String>) -> Result<T, String> {
+    let _guard = SCHEDULE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = ConfigManager::new()?.schedule_file();
+
+    let mut jobs = read_jobs(&path)?;
+    let result = f(&mut jobs)?;
+
+    // 先写临时文件再替换，避免写入中断导致所有任务丢失
+    let content =
+        serde_json::to_string_pretty(&jobs).map_err(|e| format!("序列化定时任务失败: {}", e))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("写入定时任务失败: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("替换定时任务文件失败: {}", e))?;
+    Ok(result)
+}
+
+/// 获取所有定时任务
+pub fn list_jobs() -> Result<Vec<ScheduledJob>, String> {
+    load_jobs()
+}
+
+/// 校验任务的触发方式
+fn validate_trigger(trigger: &JobTrigger) -> Result<(), String> {
+    match trigger {
+        JobTrigger::Daily { time, weekdays } => {
+            parse_time(time)?;
+            if let Some(day) = weekdays.iter().find(|d| !(1..=7).contains(*d)) {
+                return Err(format!("星期取值无效: {}（应为 1-7）", day));
+            }
+        }
+        JobTrigger::Interval { minutes } => {
+            if *minutes == 0 {
+                return Err("间隔分钟数必须大于 0".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 校验任务的操作（解析目标账户）
+///
+/// 解析账户可能会写入注册表，需要在获取任务文件锁之前调用，避免两把锁嵌套
+fn validate_action(action: &JobAction) -> Result<(), String> {
+    if let JobAction::SwitchAccount { account } = action {
+        account_registry::resolve_account(account)?;
+    }
+    Ok(())
+}
+
+/// 添加定时任务
+pub fn add_job(
+    name: Option<String>,
+    action: JobAction,
+    trigger: JobTrigger,
+    enabled: bool,
+) -> Result<ScheduledJob, String> {
+    validate_trigger(&trigger)?;
+    validate_action(&action)?;
+
+    with_jobs(|jobs| {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_usize(jobs.len());
+        let job = ScheduledJob {
+            id: format!("job_{:016x}", hasher.finish()),
+            name,
+            enabled,
+            action,
+            trigger,
+            created_at: Local::now().to_rfc3339(),
+            last_run: None,
+            last_result: None,
+            last_success: None,
+        };
+        log::info!("⏰ 添加定时任务: {} ({:?})", job.id, job.trigger);
+        jobs.push(job.clone());
+        Ok(job)
+    })
+}
+
+/// 更新定时任务，未传入的字段保持不变
+pub fn update_job(
+    id: &str,
+    name: Option<String>,
+    action: Option<JobAction>,
+    trigger: Option<JobTrigger>,
+    enabled: Option<bool>,
+) -> Result<ScheduledJob, String> {
+    if let Some(trigger) = &trigger {
+        validate_trigger(trigger)?;
+    }
+    if let Some(action) = &action {
+        validate_action(action)?;
+    }
+
+    with_jobs(|jobs| {
+        let job = jobs
+            .iter_mut()
+            .find(|j| j.id == id)
+            .ok_or_else(|| format!("未找到定时任务: {}", id))?;
+
+        if name.is_some() {
+            job.name = name;
+        }
+        if let Some(action) = action {
+            job.action = action;
+        }
+        if let Some(trigger) = trigger {
+            job.trigger = trigger;
+        }
+        if let Some(enabled) = enabled {
+            job.enabled = enabled;
+        }
+        Ok(job.clone())
+    })
+}
+
+/// 删除定时任务
+pub fn remove_job(id: &str) -> Result<(), String> {
+    with_jobs(|jobs| {
+        let before = jobs.len();
+        jobs.retain(|j| j.id != id);
+        if jobs.len() == before {
+            return Err(format!("未找到定时任务: {}", id));
+        }
+        log::info!("🗑️ 删除定时任务: {}", id);
+        Ok(())
+    })
+}
+
+/// 解析 HH:MM 格式的时间
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|e| format!("时间格式无效 ({}): {}", value, e))
+}
+
+fn parse_datetime(value: &str) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Local))
+}
+
+/// 每日任务在 `now` 之前（含）最近一次的计划执行时间
+fn latest_daily_occurrence(
+    time: NaiveTime,
+    weekdays: &[u32],
+    now: DateTime<Local>,
+) -> Option<DateTime<Local>> {
+    (0..=7).find_map(|days_ago| {
+        let date = now.date_naive() - Duration::days(days_ago);
+        if !weekdays.is_empty() && !weekdays.contains(&date.weekday().number_from_monday()) {
+            return None;
+        }
+        Local
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .filter(|t| *t <= now)
+    })
+}
+
+/// 判断任务当前是否应该执行
+fn is_due(job: &ScheduledJob, now: DateTime<Local>) -> bool {
+    if !job.enabled {
+        return false;
+    }
+
+    // 以最后一次执行时间（或创建时间）为基准，避免新建任务时立即补执行过去的计划
+    let baseline = job.last_run.as_deref().unwrap_or(job.created_at.as_str());
+    let Some(baseline) = parse_datetime(baseline) else {
+        return false;
+    };
+
+    match &job.trigger {
+        JobTrigger::Daily { time, weekdays } => {
+            let Ok(time) = parse_time(time) else {
+                return false;
+            };
+            latest_daily_occurrence(time, weekdays, now).is_some_and(|occurrence| {
+                occurrence > baseline && now - occurrence <= Duration::minutes(DAILY_GRACE_MINUTES)
+            })
+        }
+        JobTrigger::Interval { minutes } => now - baseline >= Duration::minutes(*minutes as i64),
+    }
+}
+
+/// 执行任务的操作，返回执行结果以及切换流程是否已经发送过通知
+async fn run_action(app: &AppHandle, action: &JobAction) -> (Result<String, String>, bool) {
+    let report = match action {
+        JobAction::SwitchAccount { account } => {
+            account_switcher::run_operation(
+                Some(app),
+                OperationKind::Switch,
+                Some(account.clone()),
+                None,
+            )
+            .await
+        }
+        JobAction::SwitchNext => {
+            account_switcher::switch_in_rotation(Some(app), RotationDirection::Next).await
+        }
+        // 备份期间持有操作锁，避免手动或托盘切换同时恢复、删除账户数据
+        JobAction::BackupCurrent => {
+            return (account_switcher::run_exclusive(backup_current), false);
+        }
+    };
+
+    // 切换流程执行后已发送切换通知；未能开始执行时（如已有操作进行中）由定时任务通知
+    match report {
+        Ok(report) => (report.into_result().map(|report| report.summary), true),
+        Err(e) => (Err(e), false),
+    }
+}
+
+/// 备份当前登录的账户
+fn backup_current() -> Result<String, String> {
+    match crate::antigravity_backup::read_current_email()? {
+        Some(email) => {
+            let (file_name, _) =
+                crate::antigravity_backup::smart_backup_antigravity_account(&email)?;
+            Ok(format!("已备份当前账户 {} -> {}", email, file_name))
+        }
+        None => Ok("当前未登录，跳过备份".to_string()),
+    }
+}
+
+/// 执行单个任务并记录结果
+async fn run_job(app: &AppHandle, job: ScheduledJob) {
+    log::info!("⏰ 执行定时任务: {} ({:?})", job.id, job.action);
+    let (result, notified) = run_action(app, &job.action).await;
+    let (success, message) = match result {
+        Ok(message) => (true, message),
+        Err(e) => (false, e),
+    };
+
+    if success {
+        log::info!("✅ 定时任务完成: {} - {}", job.id, message);
+    } else {
+        log::warn!("⚠️ 定时任务失败: {} - {}", job.id, message);
+    }
+
+    let recorded = with_jobs(|jobs| {
+        if let Some(stored) = jobs.iter_mut().find(|j| j.id == job.id) {
+            stored.last_run = Some(Local::now().to_rfc3339());
+            stored.last_result = Some(message.clone());
+            stored.last_success = Some(success);
+        }
+        Ok(())
+    });
+    if let Err(e) = recorded {
+        log::warn!("⚠️ 记录定时任务结果失败: {}", e);
+    }
+
+    if !notified {
+        let title = job.name.clone().unwrap_or_else(|| job.id.clone());
+        let notification = if success {
+            Ok(format!("{}: {}", title, message))
+        } else {
+            Err(format!("{}: {}", title, message))
+        };
+        crate::notifications::notify_result(
+            app,
+            crate::notifications::NotificationEvent::ScheduledJob,
+            &notification,
+        );
+    }
+
+    let payload = JobFinished {
+        job_id: job.id,
+        name: job.name,
+        success,
+        message,
+    };
+    if let Err(e) = app.emit(JOB_FINISHED_EVENT, payload) {
+        log::warn!("⚠️ 发送定时任务事件失败: {}", e);
+    }
+}
+
+/// 启动后台调度器
+///
+/// 每隔一段时间检查到期的任务并依次执行；有切换类操作正在进行时推迟到下一轮
+pub fn start_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        println!("⏰ 定时任务调度器已启动");
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_secs(TICK_INTERVAL_SECS)).await;
+
+            let jobs = match list_jobs() {
+                Ok(jobs) => jobs,
+                Err(e) => {
+                    log::warn!("⚠️ 读取定时任务失败: {}", e);
+                    continue;
+                }
+            };
+
+            let now = Local::now();
+            for job in jobs.into_iter().filter(|job| is_due(job, now)) {
+                if account_switcher::is_operation_running() {
+                    log::info!("⏸️ 有账户切换操作正在进行，推迟定时任务: {}", job.id);
+                    break;
+                }
+                run_job(&app, job).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::local;
+
+    fn job(trigger: JobTrigger, created_at: &str, last_run: Option<&str>) -> ScheduledJob {
+        ScheduledJob {
+            id: "job".to_string(),
+            name: None,
+            enabled: true,
+            action: JobAction::SwitchNext,
+            trigger,
+            created_at: local(created_at).to_rfc3339(),
+            last_run: last_run.map(|t| local(t).to_rfc3339()),
+            last_result: None,
+            last_success: None,
+        }
+    }
+
+    fn daily(time: &str, weekdays: Vec<u32>) -> JobTrigger {
+        JobTrigger::Daily {
+            time: time.to_string(),
+            weekdays,
+        }
+    }
+
+    #[test]
+    fn daily_job_runs_once_within_grace_period() {
+        let created = "2026-10-11 08:00:00";
+        let pending = job(daily("09:00", vec![]), created, None);
+        assert!(!is_due(&pending, local("2026-10-12 08:59:00")));
+        assert!(is_due(&pending, local("2026-10-12 09:00:00")));
+        assert!(is_due(&pending, local("2026-10-12 09:10:00")));
+        // 超过补执行时长后不再执行
+        assert!(!is_due(&pending, local("2026-10-12 09:11:00")));
+
+        // 当天已经执行过
+        let done = job(daily("09:00", vec![]), created, Some("2026-10-12 09:00:30"));
+        assert!(!is_due(&done, local("2026-10-12 09:05:00")));
+    }
+
+    #[test]
+    fn daily_job_not_due_right_after_creation() {
+        let created = job(daily("09:00", vec![]), "2026-10-12 09:05:00", None);
+        assert!(!is_due(&created, local("2026-10-12 09:06:00")));
+    }
+
+    #[test]
+    fn daily_job_respects_weekdays() {
+        // 2026-10-12 是周一
+        let weekends = job(daily("09:00", vec![6, 7]), "2026-10-11 10:00:00", None);
+        assert!(!is_due(&weekends, local("2026-10-12 09:01:00")));
+        assert!(is_due(&weekends, local("2026-10-17 09:01:00")));
+    }
+
+    #[test]
+    fn interval_job_uses_last_run() {
+        let trigger = JobTrigger::Interval { minutes: 30 };
+        let pending = job(trigger.clone(), "2026-10-12 09:00:00", None);
+        assert!(!is_due(&pending, local("2026-10-12 09:29:00")));
+        assert!(is_due(&pending, local("2026-10-12 09:30:00")));
+
+        let ran = job(trigger, "2026-10-12 09:00:00", Some("2026-10-12 09:30:00"));
+        assert!(!is_due(&ran, local("2026-10-12 09:45:00")));
+        assert!(is_due(&ran, local("2026-10-12 10:00:00")));
+    }
+
+    #[test]
+    fn disabled_or_invalid_job_is_never_due() {
+        let mut disabled = job(daily("09:00", vec![]), "2026-10-11 08:00:00", None);
+        disabled.enabled = false;
+        assert!(!is_due(&disabled, local("2026-10-12 09:00:00")));
+
+        let invalid = job(daily("25:99", vec![]), "2026-10-11 08:00:00", None);
+        assert!(!is_due(&invalid, local("2026-10-12 09:00:00")));
+    }
+}
@@ -0,0 +1,329 @@
+// 应用设置模块
+// 统一保存 Agent 的所有偏好设置（config.json），提供默认值、版本迁移、
+// 内存缓存和修改通知；各模块通过 `settings::get()` 读取自己的选项
+
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use crate::account_switcher::SwitchOptions;
+use crate::backup_freshness::BackupRefreshPolicy;
+use crate::config_manager::ConfigManager;
+use crate::log_monitor::{IssueCooldowns, QuotaAction};
+use crate::notifications::NotificationSettings;
+
+/// 设置变化事件，载荷为修改后的完整设置
+pub const SETTINGS_CHANGED_EVENT: &str = "settings://changed";
+
+/// 当前设置文件的版本
+///
+/// - 0：旧版 config.json，没有版本号，系统托盘开关保存在 window_state.json 中
+/// - 1：所有设置统一保存在 config.json 中
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// 内存中的设置缓存，首次读取时从文件加载（并执行迁移）；加载失败时保存错误信息
+static SETTINGS: Lazy<Mutex<Option<Result<AgentSettings, String>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// 用于发送设置变化事件的应用句柄
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+
+fn default_true() -> bool {
+    true
+}
+
+/// Agent 的所有偏好设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentSettings {
+    #[serde(default)]
+    pub version: u32,
+    /// 手动指定或自动记录的 Antigravity 可执行文件路径
+    #[serde(default)]
+    pub antigravity_path: Option<String>,
+    /// 是否启用系统托盘（关闭窗口时隐藏到托盘）
+    #[serde(default = "default_true")]
+    pub system_tray_enabled: bool,
+    #[serde(default)]
+    pub switch_options: SwitchOptions,
+    #[serde(default)]
+    pub backup_refresh: BackupRefreshPolicy,
+    #[serde(default)]
+    pub quota_action: QuotaAction,
+    /// 检测到配额 / 限流 / 认证错误后账户的冷却时长
+    #[serde(default)]
+    pub cooldowns: IssueCooldowns,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    /// 是否随系统登录自动启动
+    #[serde(default)]
+    pub launch_at_login: bool,
+    /// 开机自启时是否直接隐藏到系统托盘（仅在托盘启用时生效）
+    #[serde(default)]
+    pub start_hidden: bool,
+}
+
+impl Default for AgentSettings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            antigravity_path: None,
+            system_tray_enabled: true,
+            switch_options: SwitchOptions::default(),
+            backup_refresh: BackupRefreshPolicy::default(),
+            quota_action: QuotaAction::default(),
+            cooldowns: IssueCooldowns::default(),
+            notifications: NotificationSettings::default(),
+            launch_at_login: false,
+            start_hidden: false,
+        }
+    }
+}
+
+impl AgentSettings {
+    /// 校验设置取值
+    ///
+    /// 可执行文件路径只在修改时校验，避免 Antigravity 被卸载后其他设置也无法保存
+    fn validate(&self, previous: &AgentSettings) -> Result<(), String> {
+        if self.antigravity_path != previous.antigravity_path {
+            if let Some(path) = &self.antigravity_path {
+                if !std::path::Path::new(path).is_file() {
+                    return Err(format!("无效的 Antigravity 可执行文件路径: {}", path));
+                }
+            }
+        }
+        self.cooldowns.validate()
+    }
+}
+
+/// v0 -> v1：把旧版保存在 window_state.json 中的系统托盘开关迁移过来
+fn migrate_v0_to_v1(settings: &mut serde_json::Map<String, Value>) {
+    if settings.contains_key("systemTrayEnabled") {
+        return;
+    }
+
+    let legacy = ConfigManager::new()
+        .ok()
+        .map(|config_manager| config_manager.window_state_file())
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|value| value.get("system_tray_enabled")?.as_bool());
+
+    if let Some(enabled) = legacy {
+        println!("📦 迁移旧版系统托盘偏好: {}", enabled);
+        settings.insert("systemTrayEnabled".to_string(), Value::Bool(enabled));
+    }
+}
+
+/// 依次执行迁移，把任意旧版本的设置升级到当前版本
+///
+/// 返回升级后的设置以及是否发生了迁移
+fn migrate(mut value: Value) -> Result<(AgentSettings, bool), String> {
+    let settings = value
+        .as_object_mut()
+        .ok_or("设置文件格式无效：应为 JSON 对象")?;
+    let from = settings.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+
+    if from > SETTINGS_VERSION {
+        log::warn!(
+            "⚠️ 设置文件版本 ({}) 高于当前支持的版本 ({})，未知字段将被忽略",
+            from,
+            SETTINGS_VERSION
+        );
+    }
+
+    if from < 1 {
+        migrate_v0_to_v1(settings);
+    }
+
+    let migrated = from < SETTINGS_VERSION;
+    if migrated {
+        println!("📦 设置已从版本 {} 迁移到 {}", from, SETTINGS_VERSION);
+    }
+    settings.insert("version".to_string(), Value::from(SETTINGS_VERSION));
+
+    let settings = serde_json::from_value(value).map_err(|e| format!("解析设置失败: {}", e))?;
+    Ok((settings, migrated))
+}
+
+/// 从文件加载设置，文件不存在时也会执行迁移（读取旧版的分散配置）
+fn load_from_disk() -> Result<AgentSettings, String> {
+    let path = ConfigManager::new()?.settings_file();
+    let value = if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| format!("读取设置失败: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("解析设置失败: {}", e))?
+    } else {
+        Value::Object(serde_json::Map::new())
+    };
+
+    let (settings, migrated) = migrate(value)?;
+    if migrated {
+        write_to_disk(&settings)?;
+    }
+    Ok(settings)
+}
+
+/// 写入设置（先写临时文件再替换，避免写入中断导致文件损坏）
+fn write_to_disk(settings: &AgentSettings) -> Result<(), String> {
+    let path = ConfigManager::new()?.settings_file();
+    let content =
+        serde_json::to_string_pretty(settings).map_err(|e| format!("序列化设置失败: {}", e))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("写入设置失败: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("替换设置文件失败: {}", e))
+}
+
+/// 初始化设置：记录应用句柄用于发送变化事件，并提前加载（执行迁移）
+pub fn init(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+    let settings = get();
+    println!("⚙️ 设置已加载 (版本 {})", settings.version);
+}
+
+/// 取出缓存中的设置，尚未加载时从文件加载
+fn cached(cache: &mut Option<Result<AgentSettings, String>>) -> &Result<AgentSettings, String> {
+    cache.get_or_insert_with(|| {
+        load_from_disk().inspect_err(|e| {
+            log::warn!(
+                "⚠️ 加载设置失败，暂时使用默认设置（不会覆盖设置文件）: {}",
+                e
+            );
+        })
+    })
+}
+
+/// 获取当前设置，设置文件无效时返回默认值
+pub fn get() -> AgentSettings {
+    let mut cache = SETTINGS.lock().unwrap_or_else(|e| e.into_inner());
+    cached(&mut cache).clone().unwrap_or_default()
+}
+
+/// 在持有缓存锁的情况下生成新设置，校验通过后写入文件、更新缓存并发送变化事件
+///
+/// 设置文件无效（损坏、手动编辑出错或版本不兼容）时先重新加载一次，仍然失败则拒绝写入，
+/// 避免用默认值覆盖用户的设置文件。闭包中不能再调用 `get` / `update`，否则会死锁
+fn modify(
+    f: impl FnOnce(&AgentSettings) -> Result<AgentSettings, String>,
+) -> Result<AgentSettings, String> {
+    let settings = {
+        let mut cache = SETTINGS.lock().unwrap_or_else(|e| e.into_inner());
+        if matches!(*cache, Some(Err(_))) {
+            *cache = None;
+        }
+        let current = match cached(&mut cache) {
+            Ok(current) => current,
+            Err(e) => {
+                return Err(format!(
+                    "设置文件无效，为避免覆盖已拒绝保存（请修复或删除 config.json）: {}",
+                    e
+                ))
+            }
+        };
+        let mut settings = f(current)?;
+        settings.version = SETTINGS_VERSION;
+        settings.validate(current)?;
+        write_to_disk(&settings)?;
+        *cache = Some(Ok(settings.clone()));
+        settings
+    };
+
+    if let Some(app) = APP_HANDLE.get() {
+        if let Err(e) = app.emit(SETTINGS_CHANGED_EVENT, &settings) {
+            log::warn!("⚠️ 发送设置变化事件失败: {}", e);
+        }
+    }
+    Ok(settings)
+}
+
+/// 修改设置
+pub fn update(f: impl FnOnce(&mut AgentSettings)) -> Result<AgentSettings, String> {
+    modify(|current| {
+        let mut settings = current.clone();
+        f(&mut settings);
+        Ok(settings)
+    })
+}
+
+/// 用部分字段（JSON 对象）覆盖当前设置，嵌套对象按字段合并
+pub fn update_from_patch(patch: Value) -> Result<AgentSettings, String> {
+    if !patch.is_object() {
+        return Err("设置格式无效：应为 JSON 对象".to_string());
+    }
+
+    modify(|current| {
+        let mut value =
+            serde_json::to_value(current).map_err(|e| format!("序列化设置失败: {}", e))?;
+        merge_patch(&mut value, patch);
+        serde_json::from_value(value).map_err(|e| format!("设置格式无效: {}", e))
+    })
+}
+
+/// 合并 JSON：对象逐字段合并，其他类型直接替换
+fn merge_patch(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge_patch(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merge_patch_merges_nested_objects() {
+        let mut target = json!({
+            "systemTrayEnabled": true,
+            "cooldowns": { "quotaMinutes": 60, "authMinutes": 30 }
+        });
+        merge_patch(
+            &mut target,
+            json!({ "cooldowns": { "quotaMinutes": 10 }, "antigravityPath": null }),
+        );
+
+        assert_eq!(
+            target,
+            json!({
+                "systemTrayEnabled": true,
+                "cooldowns": { "quotaMinutes": 10, "authMinutes": 30 },
+                "antigravityPath": null
+            })
+        );
+    }
+
+    #[test]
+    fn migrate_from_v0_fills_defaults() {
+        let (settings, migrated) =
+            migrate(json!({ "systemTrayEnabled": false, "antigravityPath": "/opt/ag" })).unwrap();
+
+        assert!(migrated);
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert!(!settings.system_tray_enabled);
+        assert_eq!(settings.antigravity_path.as_deref(), Some("/opt/ag"));
+        assert_eq!(settings.cooldowns, IssueCooldowns::default());
+        assert!(!settings.launch_at_login);
+    }
+
+    #[test]
+    fn migrate_current_version_is_noop() {
+        let (settings, migrated) =
+            migrate(json!({ "version": SETTINGS_VERSION, "systemTrayEnabled": true })).unwrap();
+
+        assert!(!migrated);
+        assert!(settings.system_tray_enabled);
+    }
+
+    #[test]
+    fn migrate_rejects_invalid_files() {
+        assert!(migrate(json!([1, 2, 3])).is_err());
+        assert!(migrate(json!({ "version": 1, "systemTrayEnabled": "yes" })).is_err());
+    }
+}
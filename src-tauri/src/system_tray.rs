@@ -1,24 +1,526 @@
-use once_cell::sync::OnceCell;
-use std::sync::{Arc, Mutex};
 /// 系统托盘管理模块
 ///
 /// 使用 Tauri 2.x 内置的系统托盘 API
+use image::{Rgba, RgbaImage};
+use once_cell::sync::OnceCell;
+use std::sync::{Arc, Mutex};
 use tauri::{
     image::Image,
-    menu::{MenuBuilder, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuBuilder, MenuItem, Submenu},
     tray::TrayIconBuilder,
-    AppHandle, Manager,
+    AppHandle, Listener, Manager, Wry,
 };
 
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::account_switcher::OperationKind;
+use crate::notifications::{notify_result, NotificationEvent};
+
 /// 全局系统托盘管理器实例 - 使用 OnceCell 避免未定义行为
 static SYSTEM_TRAY_MANAGER: OnceCell<Arc<Mutex<SystemTrayManager>>> = OnceCell::new();
 
+/// 账户切换菜单项 ID 前缀，后接账户 ID
+const SWITCH_ACCOUNT_PREFIX: &str = "switch-account:";
+
+/// 托盘图标 ID，用于运行时移除图标
+const TRAY_ICON_ID: &str = "main-tray";
+
+/// 托盘图标上显示的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrayStatus {
+    /// Antigravity 正在运行且已登录
+    Running,
+    /// Antigravity 未运行
+    Stopped,
+    /// 未登录任何账户
+    LoggedOut,
+    /// 正在执行切换类操作
+    Busy,
+}
+
+impl TrayStatus {
+    /// 角标颜色
+    fn badge_color(self) -> Rgba<u8> {
+        match self {
+            TrayStatus::Running => Rgba([52, 199, 89, 255]),
+            TrayStatus::Stopped => Rgba([142, 142, 147, 255]),
+            TrayStatus::LoggedOut => Rgba([255, 149, 0, 255]),
+            TrayStatus::Busy => Rgba([0, 122, 255, 255]),
+        }
+    }
+}
+
+/// 读取托盘图标底图：优先使用 icons/tray-icon.png，不存在时使用应用默认图标
+fn load_base_icon(app_handle: &AppHandle) -> Option<RgbaImage> {
+    let tray_icon_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("icons")
+        .join("tray-icon.png");
+
+    if tray_icon_path.exists() {
+        println!("📋 尝试加载托盘图标: {}", tray_icon_path.display());
+        // 使用 image crate 处理 PNG 图像
+        match image::open(&tray_icon_path) {
+            Ok(img) => {
+                let rgba_img = img.to_rgba8();
+                println!(
+                    "✅ 托盘图标加载成功，尺寸: {}x{}",
+                    rgba_img.width(),
+                    rgba_img.height()
+                );
+                return Some(rgba_img);
+            }
+            Err(e) => println!("⚠️ 图像处理失败: {}", e),
+        }
+    } else {
+        println!("⚠️ 托盘图标文件不存在，使用默认图标");
+    }
+
+    app_handle
+        .default_window_icon()
+        .and_then(|icon| RgbaImage::from_raw(icon.width(), icon.height(), icon.rgba().to_vec()))
+}
+
+/// 在图标右下角绘制状态角标（带白色描边的圆点）
+fn badged_icon(base: &RgbaImage, status: TrayStatus) -> Image<'static> {
+    let mut icon = base.clone();
+    let (width, height) = icon.dimensions();
+    let radius = width.min(height) as f32 * 0.22;
+    let border = (radius * 0.25).max(1.0);
+    let center_x = width as f32 - radius - 1.0;
+    let center_y = height as f32 - radius - 1.0;
+    let color = status.badge_color();
+
+    for (x, y, pixel) in icon.enumerate_pixels_mut() {
+        let dx = x as f32 + 0.5 - center_x;
+        let dy = y as f32 + 0.5 - center_y;
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance <= radius - border {
+            *pixel = color;
+        } else if distance <= radius {
+            *pixel = Rgba([255, 255, 255, 255]);
+        }
+    }
+
+    Image::new_owned(icon.into_raw(), width, height)
+}
+
+/// 计算当前的托盘状态和提示文字
+///
+/// 运行状态使用进程监控最近一次的结果，不再单独扫描进程
+fn current_status() -> (TrayStatus, String) {
+    let busy = crate::account_switcher::is_operation_running();
+    let running = crate::antigravity_process::last_known_running();
+    let email = crate::antigravity_backup::read_current_email()
+        .ok()
+        .flatten();
+
+    let status = if busy {
+        TrayStatus::Busy
+    } else if email.is_none() {
+        TrayStatus::LoggedOut
+    } else if running {
+        TrayStatus::Running
+    } else {
+        TrayStatus::Stopped
+    };
+
+    let mut tooltip = format!(
+        "Antigravity Agent\n账户: {}\nAntigravity: {}",
+        email.as_deref().unwrap_or("未登录"),
+        if running { "运行中" } else { "未运行" }
+    );
+    if busy {
+        tooltip.push_str("\n正在切换账户…");
+    }
+
+    (status, tooltip)
+}
+
+/// “切换账户”子菜单中的一项
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AccountMenuItem {
+    account_id: String,
+    label: String,
+    checked: bool,
+}
+
+/// 读取账户列表，生成“切换账户”子菜单的各项，当前登录的账户打勾
+fn account_menu_items() -> Result<Vec<AccountMenuItem>, String> {
+    let registry = crate::account_registry::load_registry()?;
+
+    // 优先以数据库中实际登录的邮箱判断当前账户，读取失败时使用注册表记录
+    let live_email = crate::antigravity_backup::read_current_email()
+        .ok()
+        .flatten();
+    let items = registry
+        .rotation()
+        .into_iter()
+        .filter(|e| !e.orphaned)
+        .map(|entry| {
+            let checked = match &live_email {
+                Some(email) => entry.email.eq_ignore_ascii_case(email),
+                None => registry.current_account_id.as_deref() == Some(entry.id.as_str()),
+            };
+
+            let mut label = match entry.aliases.first() {
+                Some(alias) => format!("{} ({})", alias, entry.email),
+                None => entry.email.clone(),
+            };
+            if entry.disabled {
+                label.push_str(" [已停用]");
+            } else if entry.expired {
+                label.push_str(" [已过期]");
+            } else if entry.in_cooldown() {
+                label.push_str(" [冷却中]");
+            }
+
+            AccountMenuItem {
+                account_id: entry.id.clone(),
+                label,
+                checked,
+            }
+        })
+        .collect();
+    Ok(items)
+}
+
+/// 构建“切换账户”子菜单
+fn build_account_submenu(
+    app_handle: &AppHandle,
+    items: &Result<Vec<AccountMenuItem>, String>,
+) -> tauri::Result<Submenu<Wry>> {
+    let submenu = Submenu::with_id(app_handle, "switch-account", "切换账户", true)?;
+
+    let items = match items {
+        Ok(items) => items,
+        Err(e) => {
+            println!("⚠️ 读取账户列表失败: {}", e);
+            let item = MenuItem::with_id(
+                app_handle,
+                "switch-account-error",
+                "读取账户失败",
+                false,
+                None::<&str>,
+            )?;
+            submenu.append(&item)?;
+            return Ok(submenu);
+        }
+    };
+
+    if items.is_empty() {
+        let item = MenuItem::with_id(
+            app_handle,
+            "switch-account-empty",
+            "暂无已保存的账户",
+            false,
+            None::<&str>,
+        )?;
+        submenu.append(&item)?;
+        return Ok(submenu);
+    }
+
+    for entry in items {
+        let item = CheckMenuItem::with_id(
+            app_handle,
+            format!("{}{}", SWITCH_ACCOUNT_PREFIX, entry.account_id),
+            &entry.label,
+            true,
+            entry.checked,
+            None::<&str>,
+        )?;
+        submenu.append(&item)?;
+    }
+
+    Ok(submenu)
+}
+
+/// 构建托盘菜单
+fn build_tray_menu(
+    app_handle: &AppHandle,
+    account_items: &Result<Vec<AccountMenuItem>, String>,
+) -> tauri::Result<Menu<Wry>> {
+    let account_menu = build_account_submenu(app_handle, account_items)?;
+    let backup_item = MenuItem::with_id(
+        app_handle,
+        "backup-current",
+        "备份当前账户",
+        true,
+        None::<&str>,
+    )?;
+    let backup_restart_item = MenuItem::with_id(
+        app_handle,
+        "backup-and-restart",
+        "备份并重启 Antigravity",
+        true,
+        None::<&str>,
+    )?;
+    let logout_item = MenuItem::with_id(app_handle, "logout", "登出当前账户", true, None::<&str>)?;
+    let launch_item = MenuItem::with_id(
+        app_handle,
+        "launch-antigravity",
+        "启动 Antigravity",
+        true,
+        None::<&str>,
+    )?;
+    let kill_item = MenuItem::with_id(
+        app_handle,
+        "kill-antigravity",
+        "关闭 Antigravity",
+        true,
+        None::<&str>,
+    )?;
+    let show_item = MenuItem::with_id(app_handle, "show", "显示窗口", true, None::<&str>)?;
+    let hide_item = MenuItem::with_id(app_handle, "hide", "隐藏窗口", true, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app_handle, "quit", "退出应用", true, None::<&str>)?;
+
+    MenuBuilder::new(app_handle)
+        .item(&account_menu)
+        .separator()
+        .item(&backup_item)
+        .item(&backup_restart_item)
+        .item(&logout_item)
+        .separator()
+        .item(&launch_item)
+        .item(&kill_item)
+        .separator()
+        .item(&show_item)
+        .separator()
+        .item(&hide_item)
+        .separator()
+        .item(&quit_item)
+        .build()
+}
+
+/// 托盘菜单中执行的操作
+#[derive(Debug, Clone, Copy)]
+enum TrayAction {
+    BackupCurrent,
+    BackupAndRestart,
+    Logout,
+    Launch,
+    Kill,
+}
+
+impl TrayAction {
+    fn from_menu_id(id: &str) -> Option<Self> {
+        match id {
+            "backup-current" => Some(TrayAction::BackupCurrent),
+            "backup-and-restart" => Some(TrayAction::BackupAndRestart),
+            "logout" => Some(TrayAction::Logout),
+            "launch-antigravity" => Some(TrayAction::Launch),
+            "kill-antigravity" => Some(TrayAction::Kill),
+            _ => None,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            TrayAction::BackupCurrent => "备份当前账户",
+            TrayAction::BackupAndRestart => "备份并重启",
+            TrayAction::Logout => "登出当前账户",
+            TrayAction::Launch => "启动 Antigravity",
+            TrayAction::Kill => "关闭 Antigravity",
+        }
+    }
+
+    /// 破坏性操作需要确认，返回确认提示内容
+    fn confirmation(self) -> Option<&'static str> {
+        match self {
+            TrayAction::BackupAndRestart => {
+                Some("将关闭 Antigravity、备份当前账户并登出，然后重新启动。是否继续？")
+            }
+            TrayAction::Logout => {
+                Some("将清除 Antigravity 中的登录信息，未备份的账户数据会丢失。是否继续？")
+            }
+            TrayAction::Kill => {
+                Some("将关闭所有 Antigravity 窗口，未保存的内容可能丢失。是否继续？")
+            }
+            TrayAction::BackupCurrent | TrayAction::Launch => None,
+        }
+    }
+
+    /// 操作结果以系统通知反馈的类型，其余操作弹窗提示
+    fn notification_event(self) -> Option<NotificationEvent> {
+        match self {
+            TrayAction::BackupCurrent => Some(NotificationEvent::Backup),
+            TrayAction::Logout => Some(NotificationEvent::Logout),
+            TrayAction::BackupAndRestart | TrayAction::Launch | TrayAction::Kill => None,
+        }
+    }
+
+    /// 执行操作，复用与前端命令相同的后端函数
+    ///
+    /// 备份并重启的执行结果由切换流程发送通知，这里只在流程未能开始时返回错误
+    async fn run(self, app: &AppHandle) -> Result<String, String> {
+        match self {
+            TrayAction::BackupCurrent => {
+                let email = crate::antigravity_backup::read_current_email()?
+                    .ok_or("Antigravity 当前未登录，无法备份")?;
+                let (backup_name, is_overwrite) =
+                    crate::antigravity_backup::smart_backup_antigravity_account(&email)?;
+                let action = if is_overwrite { "更新" } else { "备份" };
+                Ok(format!("Antigravity 账户 '{}'{}成功", backup_name, action))
+            }
+            TrayAction::BackupAndRestart => crate::account_switcher::run_operation(
+                Some(app),
+                OperationKind::BackupAndRestart,
+                None,
+                None,
+            )
+            .await
+            .map(|report| report.summary),
+            TrayAction::Logout => crate::antigravity_cleanup::clear_all_antigravity_data().await,
+            TrayAction::Launch => {
+                let result = crate::antigravity_starter::start_antigravity()?;
+                crate::account_activity::start_session_for_current_account();
+                Ok(result)
+            }
+            TrayAction::Kill => crate::antigravity_process::terminate_antigravity()
+                .await
+                .map(|report| report.summary()),
+        }
+    }
+}
+
+/// 弹出操作完成提示
+fn show_action_result(app: &AppHandle, title: &str, result: Result<String, String>) {
+    let (kind, message) = match result {
+        Ok(message) => (MessageDialogKind::Info, message),
+        Err(e) => (MessageDialogKind::Error, format!("操作失败: {}", e)),
+    };
+    app.dialog()
+        .message(message)
+        .title(title)
+        .kind(kind)
+        .show(|_| {});
+}
+
+/// 执行托盘操作（需要确认的操作先弹窗确认）
+fn run_tray_action(app: &AppHandle, action: TrayAction) {
+    println!("📋 菜单: {}", action.title());
+
+    let execute = move |app: AppHandle| {
+        tauri::async_runtime::spawn(async move {
+            let result = action.run(&app).await;
+            match action.notification_event() {
+                Some(event) => notify_result(&app, event, &result),
+                None if matches!(action, TrayAction::BackupAndRestart) => {
+                    if result.is_err() {
+                        show_action_result(&app, action.title(), result);
+                    }
+                }
+                None => show_action_result(&app, action.title(), result),
+            }
+            SystemTrayManager::refresh_menu();
+            SystemTrayManager::refresh_status();
+        });
+    };
+
+    match action.confirmation() {
+        Some(message) => {
+            let app_handle = app.clone();
+            app.dialog()
+                .message(message)
+                .title(action.title())
+                .kind(MessageDialogKind::Warning)
+                .buttons(MessageDialogButtons::OkCancelCustom(
+                    "继续".to_string(),
+                    "取消".to_string(),
+                ))
+                .show(move |confirmed| {
+                    if confirmed {
+                        execute(app_handle);
+                    }
+                });
+        }
+        None => execute(app.clone()),
+    }
+}
+
+/// 从托盘菜单切换账户（不打开主窗口）
+///
+/// 点击勾选菜单项时系统会直接翻转勾选状态，因此无论切换结果如何都强制重建菜单
+fn switch_account_from_tray(app: &AppHandle, account_id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        println!("📋 菜单: 切换账户 {}", account_id);
+
+        // 点击的是当前登录的账户，不需要重启 Antigravity
+        let is_live = account_menu_items().is_ok_and(|items| {
+            items
+                .iter()
+                .any(|item| item.checked && item.account_id == account_id)
+        });
+        if is_live {
+            println!("📋 已是当前登录的账户，跳过切换");
+            SystemTrayManager::rebuild_menu();
+            return;
+        }
+
+        match crate::account_switcher::run_operation(
+            Some(&app),
+            OperationKind::Switch,
+            Some(account_id),
+            None,
+        )
+        .await
+        {
+            // 切换结果由切换流程发送通知，这里只提示未能开始切换的错误
+            Ok(report) => println!("📋 托盘切换账户结束: {}", report.summary),
+            Err(e) => show_action_result(&app, "切换账户", Err(e)),
+        }
+        // 无论成功与否都重建菜单，使勾选状态与实际登录账户一致
+        SystemTrayManager::rebuild_menu();
+    });
+}
+
+/// 处理托盘菜单事件
+fn handle_menu_event(app: &AppHandle, id: &str) {
+    if let Some(account_id) = id.strip_prefix(SWITCH_ACCOUNT_PREFIX) {
+        switch_account_from_tray(app, account_id.to_string());
+        return;
+    }
+
+    if let Some(action) = TrayAction::from_menu_id(id) {
+        run_tray_action(app, action);
+        return;
+    }
+
+    match id {
+        "show" => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+                println!("📋 菜单: 显示窗口");
+            }
+        }
+        "hide" => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.hide();
+                println!("📋 菜单: 隐藏窗口");
+            }
+        }
+        "quit" => {
+            println!("📋 菜单: 退出应用");
+            app.exit(0);
+        }
+        _ => {
+            println!("🖱️ 未知菜单项: {:?}", id);
+        }
+    }
+}
+
 /// 系统托盘管理器
 pub struct SystemTrayManager {
     is_enabled: bool,
     app_handle: Option<AppHandle>,
     tray_icon: Option<tauri::tray::TrayIcon>,
     is_minimizing: bool, // 防止重入的标志
+    base_icon: Option<RgbaImage>,
+    /// 最近一次显示的状态和提示文字，未变化时不重复更新
+    last_status: Option<(TrayStatus, String)>,
+    /// 当前菜单中的账户项，未变化时不重建菜单
+    last_menu_items: Option<Result<Vec<AccountMenuItem>, String>>,
 }
 
 impl SystemTrayManager {
@@ -29,6 +531,9 @@ impl SystemTrayManager {
             app_handle: None,
             tray_icon: None,
             is_minimizing: false,
+            base_icon: None,
+            last_status: None,
+            last_menu_items: None,
         }
     }
 
@@ -42,95 +547,16 @@ impl SystemTrayManager {
         let mut manager = SystemTrayManager::new();
         manager.app_handle = Some(app_handle.clone());
 
-        // 创建托盘图标
-        println!("📋 创建系统托盘图标");
-
-        // 尝试读取托盘图标
-        let tray_icon_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .join("icons")
-            .join("tray-icon.png");
-
-        // 创建菜单项
-        let show_item = MenuItem::with_id(app_handle, "show", "显示窗口", true, None::<&str>)?;
-        let hide_item = MenuItem::with_id(app_handle, "hide", "隐藏窗口", true, None::<&str>)?;
-        let quit_item = MenuItem::with_id(app_handle, "quit", "退出应用", true, None::<&str>)?;
-
-        let menu = MenuBuilder::new(app_handle)
-            .item(&show_item)
-            .separator()
-            .item(&hide_item)
-            .separator()
-            .item(&quit_item)
-            .build()?;
+        // 读取托盘图标（作为绘制状态角标的底图）
+        manager.base_icon = load_base_icon(app_handle);
 
-        // 构建托盘图标
-        let mut tray_builder = TrayIconBuilder::new()
-            .menu(&menu)
-            .tooltip("Antigravity Agent");
-
-        // 如果图标文件存在，加载图标
-        if tray_icon_path.exists() {
-            println!("📋 尝试加载托盘图标: {}", tray_icon_path.display());
-            match std::fs::read(&tray_icon_path) {
-                Ok(icon_data) => {
-                    // 使用 image crate 处理 PNG 图像
-                    match image::load_from_memory(&icon_data) {
-                        Ok(img) => {
-                            let rgba_img = img.to_rgba8();
-                            let (width, height) = rgba_img.dimensions();
-                            let rgba_data = rgba_img.into_raw();
-
-                            // 创建 Tauri Image
-                            let tauri_image = Image::new_owned(rgba_data, width, height);
-                            tray_builder = tray_builder.icon(tauri_image);
-                            println!("✅ 托盘图标加载成功，尺寸: {}x{}", width, height);
-                        }
-                        Err(e) => {
-                            println!("⚠️ 图像处理失败: {}", e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    println!("⚠️ 读取图标文件失败: {}", e);
-                }
-            }
-        } else {
-            println!("⚠️ 托盘图标文件不存在，使用默认图标");
-        }
-
-        // 创建托盘图标
-        match tray_builder.build(app_handle) {
-            Ok(tray) => {
-                manager.tray_icon = Some(tray.clone());
-                println!("✅ 系统托盘图标创建成功");
-
-                // 设置菜单事件监听
-                tray.on_menu_event(move |app, event| match event.id().as_ref() {
-                    "show" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            println!("📋 菜单: 显示窗口");
-                        }
-                    }
-                    "hide" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.hide();
-                            println!("📋 菜单: 隐藏窗口");
-                        }
-                    }
-                    "quit" => {
-                        println!("📋 菜单: 退出应用");
-                        app.exit(0);
-                    }
-                    _ => {
-                        println!("🖱️ 未知菜单项: {:?}", event.id());
-                    }
-                });
-            }
-            Err(e) => {
+        // 按保存的偏好决定是否创建托盘图标
+        if crate::settings::get().system_tray_enabled {
+            if let Err(e) = manager.enable() {
                 println!("⚠️ 创建系统托盘图标失败: {}", e);
             }
+        } else {
+            println!("📋 系统托盘已被用户禁用，跳过创建托盘图标");
         }
 
         // 使用 OnceCell 安全地设置全局实例
@@ -140,7 +566,13 @@ impl SystemTrayManager {
             return Ok(());
         }
 
+        // 账户列表或当前账户变化后重建托盘菜单
+        app_handle.listen(crate::account_registry::REGISTRY_CHANGED_EVENT, |_| {
+            Self::refresh_menu();
+        });
+
         println!("✅ 系统托盘管理器初始化成功");
+        Self::refresh_status();
         Ok(())
     }
 
@@ -149,20 +581,158 @@ impl SystemTrayManager {
         SYSTEM_TRAY_MANAGER.get().cloned()
     }
 
-    /// 启用系统托盘功能
+    /// 根据当前登录账户和 Antigravity 运行状态更新托盘提示文字和图标角标
+    ///
+    /// 托盘未初始化时直接忽略
+    pub fn refresh_status() {
+        let Some(manager) = Self::get_global() else {
+            return;
+        };
+
+        // 先在锁外读取状态，避免检测进程和读取数据库时长时间持有锁
+        let (status, tooltip) = current_status();
+
+        let Ok(mut manager) = manager.lock() else {
+            return;
+        };
+        if manager.last_status.as_ref() == Some(&(status, tooltip.clone())) {
+            return;
+        }
+
+        if let Some(tray) = &manager.tray_icon {
+            if let Err(e) = tray.set_tooltip(Some(&tooltip)) {
+                println!("⚠️ 更新托盘提示失败: {}", e);
+            }
+            if let Some(base_icon) = &manager.base_icon {
+                if let Err(e) = tray.set_icon(Some(badged_icon(base_icon, status))) {
+                    println!("⚠️ 更新托盘图标失败: {}", e);
+                }
+            }
+        }
+        manager.last_status = Some((status, tooltip));
+    }
+
+    /// 重建托盘菜单（账户新增、删除或切换后调用）
+    ///
+    /// 只有账户列表、显示文字或勾选状态变化时才重建；托盘未初始化时直接忽略
+    pub fn refresh_menu() {
+        Self::update_menu(false);
+    }
+
+    /// 强制重建托盘菜单，恢复被点击翻转的勾选状态
+    fn rebuild_menu() {
+        Self::update_menu(true);
+    }
+
+    fn update_menu(force: bool) {
+        let Some(manager) = Self::get_global() else {
+            return;
+        };
+
+        // 先在锁外读取账户列表，避免读取注册表和数据库时长时间持有锁
+        let items = account_menu_items();
+
+        let Ok(mut manager) = manager.lock() else {
+            return;
+        };
+        if !force && manager.last_menu_items.as_ref() == Some(&items) {
+            return;
+        }
+
+        if let (Some(app_handle), Some(tray)) = (&manager.app_handle, &manager.tray_icon) {
+            match build_tray_menu(app_handle, &items) {
+                Ok(menu) => {
+                    if let Err(e) = tray.set_menu(Some(menu)) {
+                        println!("⚠️ 更新托盘菜单失败: {}", e);
+                        return;
+                    }
+                }
+                Err(e) => {
+                    println!("⚠️ 构建托盘菜单失败: {}", e);
+                    return;
+                }
+            }
+            manager.last_menu_items = Some(items);
+        }
+    }
+
+    /// 创建托盘图标（包含菜单和事件监听）
+    fn create_tray_icon(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let app_handle = self.app_handle.as_ref().ok_or("系统托盘管理器未初始化")?;
+
+        println!("📋 创建系统托盘图标");
+
+        // 创建菜单（包含动态的账户切换子菜单）
+        let items = account_menu_items();
+        let menu = build_tray_menu(app_handle, &items)?;
+
+        // 构建托盘图标
+        let mut tray_builder = TrayIconBuilder::with_id(TRAY_ICON_ID)
+            .menu(&menu)
+            .tooltip("Antigravity Agent");
+
+        if let Some(base_icon) = &self.base_icon {
+            let (width, height) = base_icon.dimensions();
+            tray_builder = tray_builder.icon(Image::new_owned(
+                base_icon.clone().into_raw(),
+                width,
+                height,
+            ));
+        }
+
+        let tray = tray_builder.build(app_handle)?;
+
+        // 设置菜单事件监听
+        tray.on_menu_event(move |app, event| handle_menu_event(app, event.id().as_ref()));
+
+        self.tray_icon = Some(tray);
+        self.last_menu_items = Some(items);
+        // 新图标需要重新应用状态角标和提示文字
+        self.last_status = None;
+        println!("✅ 系统托盘图标创建成功");
+        Ok(())
+    }
+
+    /// 启用系统托盘功能（托盘图标不存在时创建）
     pub fn enable(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.tray_icon.is_none() {
+            self.create_tray_icon()?;
+        }
         self.is_enabled = true;
         println!("✅ 系统托盘功能已启用");
         Ok(())
     }
 
-    /// 禁用系统托盘功能
+    /// 禁用系统托盘功能并移除托盘图标
+    ///
+    /// 窗口此时处于隐藏状态时重新显示，避免应用失去入口
     pub fn disable(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         self.is_enabled = false;
-        // 移除托盘图标
-        if let Some(_tray) = self.tray_icon.take() {
+
+        if let Some(tray) = self.tray_icon.take() {
+            // 先隐藏再从 Tauri 内部状态中移除，确保图标立即消失
+            if let Err(e) = tray.set_visible(false) {
+                println!("⚠️ 隐藏系统托盘图标失败: {}", e);
+            }
+            if let Some(app_handle) = &self.app_handle {
+                app_handle.remove_tray_by_id(TRAY_ICON_ID);
+            }
             println!("🔴 系统托盘图标已移除");
         }
+        self.last_status = None;
+        self.last_menu_items = None;
+
+        if let Some(window) = self
+            .app_handle
+            .as_ref()
+            .and_then(|app_handle| app_handle.get_webview_window("main"))
+        {
+            if !window.is_visible().unwrap_or(true) {
+                window.show()?;
+                window.set_focus()?;
+            }
+        }
+
         println!("🔴 系统托盘功能已禁用");
         Ok(())
     }
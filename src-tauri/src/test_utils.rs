@@ -0,0 +1,43 @@
+// 单元测试辅助模块
+// 各模块测试共用的临时目录、本地时间和账户条目构造函数
+
+use chrono::{DateTime, Local, TimeZone};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::account_registry::{sanitize_file_stem, AccountEntry};
+
+/// 每个测试使用独立的临时目录（`name` 在所有测试中需唯一）
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "antigravity-agent-test-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 解析 `YYYY-MM-DD HH:MM:SS` 格式的本地时间
+pub fn local(value: &str) -> DateTime<Local> {
+    let naive = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap();
+    Local.from_local_datetime(&naive).earliest().unwrap()
+}
+
+/// 构造一个没有任何标记的账户条目
+pub fn entry(id: &str, email: &str) -> AccountEntry {
+    AccountEntry {
+        id: id.to_string(),
+        email: email.to_string(),
+        file_name: sanitize_file_stem(email),
+        aliases: Vec::new(),
+        created_at: String::new(),
+        last_switched: None,
+        disabled: false,
+        expired: false,
+        cooldown_until: None,
+        cooldown_reason: None,
+        orphaned: false,
+    }
+}
@@ -1,161 +1,178 @@
 // 窗口事件处理模块
-// 负责在应用启动时恢复窗口状态
+// 负责在应用启动时恢复窗口状态，并在窗口移动、缩放、关闭和应用退出时保存
 
 use crate::window_state_manager::{load_window_state, save_window_state, WindowState};
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
-use tauri::Manager;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering::SeqCst};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+/// 窗口停止变化后延迟保存的时间（尾部防抖，保证最后一次移动或缩放会被保存）
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 窗口至少要有这么多逻辑像素落在显示器可用区域内，否则视为在屏幕外
+const MIN_VISIBLE: f64 = 100.0;
+
+/// 窗口变化计数，延迟保存时用来判断之后是否还有新的变化
+static SAVE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 恢复标志，恢复窗口状态期间产生的窗口事件不触发保存
+static IS_RESTORING: AtomicBool = AtomicBool::new(true);
+
+/// 判断物理坐标点是否在显示器范围内
+fn monitor_contains(monitor: &Monitor, point: PhysicalPosition<i32>) -> bool {
+    let position = monitor.position();
+    let size = monitor.size();
+    point.x >= position.x
+        && point.y >= position.y
+        && point.x < position.x + size.width as i32
+        && point.y < position.y + size.height as i32
+}
+
+/// 根据当前连接的显示器计算窗口应恢复到的物理位置和大小
+///
+/// 优先使用保存时所在的显示器（按名称匹配），找不到时使用包含窗口左上角的显示器；
+/// 窗口大小按目标显示器的缩放比例换算并限制在可用区域内，窗口大部分在屏幕外时居中。
+/// 没有匹配的显示器时返回 None
+fn fit_to_monitors(
+    state: &WindowState,
+    monitors: &[Monitor],
+) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    let saved_scale = state.scale_factor.unwrap_or(1.0);
+    let position = PhysicalPosition::new(
+        (state.x * saved_scale).round() as i32,
+        (state.y * saved_scale).round() as i32,
+    );
+
+    let monitor = monitors
+        .iter()
+        .find(|m| state.monitor.is_some() && m.name() == state.monitor.as_ref())
+        .or_else(|| monitors.iter().find(|m| monitor_contains(m, position)))?;
+
+    let area = monitor.work_area();
+    // 旧版本保存的是物理像素，直接使用
+    let scale = match state.scale_factor {
+        Some(_) => monitor.scale_factor(),
+        None => 1.0,
+    };
+    let size = PhysicalSize::new(
+        ((state.width * scale).round() as u32).min(area.size.width),
+        ((state.height * scale).round() as u32).min(area.size.height),
+    );
+
+    // 计算窗口与可用区域在水平和垂直方向上的重叠长度
+    let overlap = |start: i32, len: u32, area_start: i32, area_len: u32| {
+        let end = (start + len as i32).min(area_start + area_len as i32);
+        (end - start.max(area_start)) as f64
+    };
+    let min_visible = MIN_VISIBLE * monitor.scale_factor();
+    let visible = overlap(position.x, size.width, area.position.x, area.size.width) >= min_visible
+        && overlap(position.y, size.height, area.position.y, area.size.height) >= min_visible;
+
+    let position = if visible {
+        position
+    } else {
+        PhysicalPosition::new(
+            area.position.x + (area.size.width - size.width) as i32 / 2,
+            area.position.y + (area.size.height - size.height) as i32 / 2,
+        )
+    };
+
+    Some((position, size))
+}
+
+/// 恢复上次保存的窗口状态
+async fn restore_window_state(window: &WebviewWindow) {
+    let saved_state = match load_window_state() {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("⚠️ 加载窗口状态失败: {}，将使用默认状态", e);
+            println!("✅ 使用默认窗口状态");
+            return;
+        }
+    };
+
+    println!(
+        "🔄 恢复窗口状态: 位置({:.1}, {:.1}), 大小({:.1}x{:.1}), 最大化:{}, 显示器:{}",
+        saved_state.x,
+        saved_state.y,
+        saved_state.width,
+        saved_state.height,
+        saved_state.maximized,
+        saved_state.monitor.as_deref().unwrap_or("未知")
+    );
+
+    let monitors = window.available_monitors().unwrap_or_default();
+    match fit_to_monitors(&saved_state, &monitors) {
+        Some((position, size)) => {
+            // 先设置大小再设置位置，避免窗口跨显示器时按旧的缩放比例调整大小
+            if let Err(e) = window.set_size(tauri::Size::Physical(size)) {
+                eprintln!("⚠️ 恢复窗口大小失败: {}，将使用默认大小", e);
+            }
+            if let Err(e) = window.set_position(tauri::Position::Physical(position)) {
+                eprintln!("⚠️ 恢复窗口位置失败: {}，将使用默认位置", e);
+            }
+        }
+        None => {
+            println!("⚠️ 保存的显示器已不可用，窗口将居中显示");
+            if let Err(e) = window.set_size(tauri::Size::Logical(tauri::LogicalSize {
+                width: saved_state.width,
+                height: saved_state.height,
+            })) {
+                eprintln!("⚠️ 恢复窗口大小失败: {}，将使用默认大小", e);
+            }
+            if let Err(e) = window.center() {
+                eprintln!("⚠️ 窗口居中失败: {}", e);
+            }
+        }
+    }
+
+    // 如果之前是最大化状态，则恢复最大化
+    if saved_state.maximized {
+        if let Err(e) = window.maximize() {
+            eprintln!("⚠️ 恢复窗口最大化状态失败: {}", e);
+        } else {
+            println!("✅ 窗口状态恢复完成（包含最大化）");
+        }
+    } else {
+        println!("✅ 窗口状态恢复完成");
+    }
+}
 
 /// 初始化窗口事件处理器
 pub fn init_window_event_handler(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     // 获取主窗口
     let main_window = app.get_webview_window("main").ok_or("无法获取主窗口")?;
 
-    // 创建保存状态的共享状态，用于防抖和恢复标志
-    let last_save_time = Arc::new(Mutex::new(Instant::now()));
-    let is_restoring = Arc::new(Mutex::new(true)); // 恢复标志，防止保存状态
-
     // 应用启动时，尝试恢复上次保存的窗口状态
     let window_clone = main_window.clone();
-    let is_restoring_clone = is_restoring.clone();
     tauri::async_runtime::spawn(async move {
-        match load_window_state().await {
-            Ok(saved_state) => {
-                println!(
-                    "🔄 恢复窗口状态: 位置({:.1}, {:.1}), 大小({:.1}x{:.1}), 最大化:{}",
-                    saved_state.x,
-                    saved_state.y,
-                    saved_state.width,
-                    saved_state.height,
-                    saved_state.maximized
-                );
-
-                // 设置窗口位置
-                if let Err(e) =
-                    window_clone.set_position(tauri::Position::Physical(tauri::PhysicalPosition {
-                        x: saved_state.x as i32,
-                        y: saved_state.y as i32,
-                    }))
-                {
-                    eprintln!("⚠️ 恢复窗口位置失败: {}，将使用默认位置", e);
-                }
-
-                // 设置窗口大小
-                if let Err(e) = window_clone.set_size(tauri::Size::Physical(tauri::PhysicalSize {
-                    width: saved_state.width as u32,
-                    height: saved_state.height as u32,
-                })) {
-                    eprintln!("⚠️ 恢复窗口大小失败: {}，将使用默认大小", e);
-                }
-
-                // 如果之前是最大化状态，则恢复最大化
-                if saved_state.maximized {
-                    if let Err(e) = window_clone.maximize() {
-                        eprintln!("⚠️ 恢复窗口最大化状态失败: {}", e);
-                    } else {
-                        println!("✅ 窗口状态恢复完成（包含最大化）");
-                    }
-                } else {
-                    println!("✅ 窗口状态恢复完成");
-                }
-            }
-            Err(e) => {
-                eprintln!("⚠️ 加载窗口状态失败: {}，将使用默认状态", e);
-                println!("✅ 使用默认窗口状态");
-            }
-        }
+        restore_window_state(&window_clone).await;
 
         // 恢复完成后，等待一小段时间确保所有窗口事件都处理完毕，然后清除恢复标志
         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-        // 安全的锁获取，避免毒化锁 panic
-        match is_restoring_clone.lock() {
-            Ok(mut flag) => {
-                *flag = false;
-                println!("✅ 窗口状态恢复标志已清除，开始响应窗口变化事件");
-            }
-            Err(_) => {
-                eprintln!("⚠️ 恢复标志锁中毒，无法清除标志");
-            }
-        }
+        IS_RESTORING.store(false, SeqCst);
+        println!("✅ 窗口状态恢复标志已清除，开始响应窗口变化事件");
     });
 
     // 监听窗口事件，包括大小变化、移动和关闭
     let window_for_events = main_window.clone();
-    let last_save_for_events = last_save_time.clone();
-    let is_restoring_for_events = is_restoring.clone();
 
     window_for_events.clone().on_window_event(move |event| {
         match event {
-            // 窗口大小变化时保存状态
-            tauri::WindowEvent::Resized { .. } => {
-                let window = window_for_events.clone();
-                let last_save = last_save_for_events.clone();
-                let restoring = is_restoring_for_events.clone();
-                tauri::async_runtime::spawn(async move {
-                    // 检查是否正在恢复状态，如果是则跳过保存
-                    {
-                        match restoring.lock() {
-                            Ok(is_restoring_flag) => {
-                                if *is_restoring_flag {
-                                    return;
-                                }
-                            }
-                            Err(_) => {
-                                eprintln!("⚠️ 恢复标志锁中毒，继续执行保存操作");
-                            }
-                        }
-                    }
-
-                    // 防抖：避免频繁保存
-                    {
-                        let mut last_save_time = last_save.lock().unwrap();
-                        if last_save_time.elapsed() < Duration::from_secs(1) {
-                            return;
-                        }
-                        *last_save_time = Instant::now();
-                    }
-
-                    save_current_window_state(&window).await;
-                });
-            }
-            // 窗口移动时保存状态
-            tauri::WindowEvent::Moved { .. } => {
-                let window = window_for_events.clone();
-                let last_save = last_save_for_events.clone();
-                let restoring = is_restoring_for_events.clone();
-                tauri::async_runtime::spawn(async move {
-                    // 检查是否正在恢复状态，如果是则跳过保存
-                    {
-                        match restoring.lock() {
-                            Ok(is_restoring_flag) => {
-                                if *is_restoring_flag {
-                                    return;
-                                }
-                            }
-                            Err(_) => {
-                                eprintln!("⚠️ 恢复标志锁中毒，继续执行保存操作");
-                            }
-                        }
-                    }
-
-                    // 防抖：避免频繁保存
-                    {
-                        let mut last_save_time = last_save.lock().unwrap();
-                        if last_save_time.elapsed() < Duration::from_secs(1) {
-                            return;
-                        }
-                        *last_save_time = Instant::now();
-                    }
-
-                    save_current_window_state(&window).await;
-                });
+            // 窗口大小变化、移动或切换到缩放比例不同的显示器时延迟保存状态
+            // 注意：Tauri 2.x 中没有 Maximized/Unmaximized 事件，最大化状态在 Resized 事件中处理
+            tauri::WindowEvent::Resized { .. }
+            | tauri::WindowEvent::Moved { .. }
+            | tauri::WindowEvent::ScaleFactorChanged { .. } => {
+                schedule_save(window_for_events.clone());
             }
-            // 注意：Tauri 2.x 中没有 Maximized/Unmaximized 事件
-            // 最大化/还原状态会在 Resized 事件中捕获和处理
             // 窗口关闭时处理系统托盘逻辑
             tauri::WindowEvent::CloseRequested { api, .. } => {
                 println!("🚪 收到窗口关闭请求事件");
 
+                // 无论是隐藏到托盘还是关闭，都先立即保存当前状态
+                flush_window_state(window_for_events.app_handle());
+
                 // 检查系统托盘是否启用
                 if let Some(manager) = crate::system_tray::SystemTrayManager::get_global() {
                     match manager.lock() {
@@ -167,7 +184,6 @@ pub fn init_window_event_handler(app: &tauri::App) -> Result<(), Box<dyn std::er
                                 api.prevent_close();
 
                                 // 最小化到系统托盘 - 使用 std::thread::spawn 避免异步锁竞争
-                                let _window = window_for_events.clone();
                                 std::thread::spawn(move || {
                                     // 在新线程中同步调用，避免异步上下文中的锁竞争
                                     if let Some(manager) =
@@ -197,12 +213,6 @@ pub fn init_window_event_handler(app: &tauri::App) -> Result<(), Box<dyn std::er
                 }
 
                 println!("📋 系统托盘未启用，允许关闭窗口");
-
-                // 如果系统托盘未启用，保存状态并允许关闭
-                let window = window_for_events.clone();
-                tauri::async_runtime::spawn(async move {
-                    save_current_window_state(&window).await;
-                });
             }
             _ => {}
         }
@@ -211,24 +221,84 @@ pub fn init_window_event_handler(app: &tauri::App) -> Result<(), Box<dyn std::er
     Ok(())
 }
 
-/// 保存当前窗口状态的辅助函数
-async fn save_current_window_state(window: &tauri::WebviewWindow) {
-    if let (Ok(outer_position), Ok(outer_size), Ok(is_maximized)) = (
-        window.outer_position(),
-        window.outer_size(),
-        window.is_maximized(),
-    ) {
-        let current_state = WindowState {
-            x: outer_position.x as f64,
-            y: outer_position.y as f64,
-            width: outer_size.width as f64,
-            height: outer_size.height as f64,
-            maximized: is_maximized,
-            system_tray_enabled: true, // 这里使用默认值，因为系统托盘状态有专门的持久化机制
-        };
-
-        if let Err(e) = save_window_state(current_state).await {
-            eprintln!("保存窗口状态失败: {}", e);
+/// 窗口变化后延迟保存：只有最后一次变化之后 `SAVE_DEBOUNCE` 内没有新变化才真正写入
+fn schedule_save(window: WebviewWindow) {
+    if IS_RESTORING.load(SeqCst) {
+        return;
+    }
+
+    let generation = SAVE_GENERATION.fetch_add(1, SeqCst) + 1;
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DEBOUNCE).await;
+        // 期间有新的变化，交给最后一次变化的任务保存
+        if SAVE_GENERATION.load(SeqCst) != generation {
+            return;
         }
+        save_current_window_state(&window);
+    });
+}
+
+/// 立即保存主窗口状态并取消尚未执行的延迟保存（窗口关闭和应用退出时调用）
+pub fn flush_window_state(app: &AppHandle) {
+    if IS_RESTORING.load(SeqCst) {
+        return;
+    }
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    SAVE_GENERATION.fetch_add(1, SeqCst);
+    save_current_window_state(&window);
+}
+
+/// 读取窗口当前的状态
+///
+/// 窗口最小化或隐藏到托盘时位置没有意义（Windows 下为 -32000），返回 None；
+/// 最大化时保留之前保存的普通窗口位置和大小，只更新最大化标志
+fn capture_window_state(window: &WebviewWindow) -> Option<WindowState> {
+    if window.is_minimized().unwrap_or(false) || !window.is_visible().unwrap_or(true) {
+        return None;
+    }
+
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+
+    if window.is_maximized().ok()? {
+        let mut state = load_window_state().unwrap_or_default();
+        state.maximized = true;
+        state.monitor = monitor.or(state.monitor);
+        return Some(state);
+    }
+
+    let scale_factor = window.scale_factor().ok()?;
+    let position = window
+        .outer_position()
+        .ok()?
+        .to_logical::<f64>(scale_factor);
+    // 恢复时 set_size 设置的是内部尺寸，这里也保存内部尺寸
+    let size = window.inner_size().ok()?.to_logical::<f64>(scale_factor);
+
+    Some(WindowState {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized: false,
+        monitor,
+        scale_factor: Some(scale_factor),
+    })
+}
+
+/// 保存当前窗口状态的辅助函数
+fn save_current_window_state(window: &WebviewWindow) {
+    let Some(current_state) = capture_window_state(window) else {
+        return;
+    };
+
+    if let Err(e) = save_window_state(current_state) {
+        eprintln!("保存窗口状态失败: {}", e);
     }
 }
@@ -6,7 +6,13 @@ use std::fs;
 
 use crate::config_manager::ConfigManager;
 
+/// 窗口允许的最小逻辑尺寸
+const MIN_WIDTH: f64 = 400.0;
+const MIN_HEIGHT: f64 = 400.0;
+
 // 窗口状态结构
+//
+// 位置和大小均为逻辑像素（物理像素 / 缩放比例），换到缩放比例不同的显示器时大小保持一致
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WindowState {
     pub x: f64,
@@ -14,7 +20,12 @@ pub struct WindowState {
     pub width: f64,
     pub height: f64,
     pub maximized: bool,
-    pub system_tray_enabled: bool,
+    /// 保存时窗口所在显示器的名称
+    #[serde(default)]
+    pub monitor: Option<String>,
+    /// 保存时窗口所在显示器的缩放比例（旧版本保存的是物理像素，没有该字段）
+    #[serde(default)]
+    pub scale_factor: Option<f64>,
 }
 
 impl Default for WindowState {
@@ -25,7 +36,8 @@ impl Default for WindowState {
             width: 800.0,
             height: 600.0,
             maximized: false,
-            system_tray_enabled: true, // 默认启用系统托盘
+            monitor: None,
+            scale_factor: None,
         }
     }
 }
@@ -33,27 +45,25 @@ impl Default for WindowState {
 impl WindowState {
     /// 验证窗口状态是否有效
     ///
-    /// 过滤以下无效状态：
-    /// - 窗口位置超出合理范围（如 -32000，表示窗口被隐藏）
-    /// - 窗口大小过小（宽度或高度 < 400）
-    /// - 窗口大小过大（宽度 > 4000 或高度 > 3000）
+    /// 只过滤明显异常的值（非有限数、过小的窗口、非正的缩放比例）；
+    /// 位置是否可见在恢复时根据实际连接的显示器判断
     pub fn is_valid(&self) -> bool {
-        // 检查位置是否在合理范围内（-1000 到 10000）
-        let position_valid =
-            self.x > -1000.0 && self.x < 10000.0 && self.y > -1000.0 && self.y < 10000.0;
-
-        // 检查窗口大小是否合理（400x400 到 4000x3000）
-        let size_valid = self.width >= 400.0
-            && self.width <= 4000.0
-            && self.height >= 400.0
-            && self.height <= 3000.0;
-
-        position_valid && size_valid
+        let finite = [self.x, self.y, self.width, self.height]
+            .iter()
+            .all(|v| v.is_finite());
+        let size_valid = self.width >= MIN_WIDTH && self.height >= MIN_HEIGHT;
+        let scale_valid = self
+            .scale_factor
+            .is_none_or(|scale| scale.is_finite() && scale > 0.0);
+
+        finite && size_valid && scale_valid
     }
 }
 
 /// 保存窗口状态
-pub async fn save_window_state(state: WindowState) -> Result<(), String> {
+///
+/// 同步写入文件，窗口关闭和应用退出时可以在事件回调中直接调用
+pub fn save_window_state(state: WindowState) -> Result<(), String> {
     // 验证窗口状态是否有效，拒绝保存异常值
     if !state.is_valid() {
         println!(
@@ -73,15 +83,20 @@ pub async fn save_window_state(state: WindowState) -> Result<(), String> {
     fs::write(state_file, json_content).map_err(|e| format!("保存窗口状态失败: {}", e))?;
 
     println!(
-        "💾 窗口状态已保存: 位置({:.1}, {:.1}), 大小({:.1}x{:.1}), 最大化:{}",
-        state.x, state.y, state.width, state.height, state.maximized
+        "💾 窗口状态已保存: 位置({:.1}, {:.1}), 大小({:.1}x{:.1}), 最大化:{}, 显示器:{}",
+        state.x,
+        state.y,
+        state.width,
+        state.height,
+        state.maximized,
+        state.monitor.as_deref().unwrap_or("未知")
     );
 
     Ok(())
 }
 
 /// 加载窗口状态
-pub async fn load_window_state() -> Result<WindowState, String> {
+pub fn load_window_state() -> Result<WindowState, String> {
     // 使用 ConfigManager 统一管理配置目录
     let config_manager = ConfigManager::new()?;
     let state_file = config_manager.window_state_file();
@@ -109,52 +124,18 @@ pub async fn load_window_state() -> Result<WindowState, String> {
 }
 
 /// 保存系统托盘启用状态
+///
+/// 托盘偏好保存在应用设置中，与窗口位置和大小分开，保存窗口状态时不会被覆盖
 pub async fn save_system_tray_state(enabled: bool) -> Result<(), String> {
-    // 使用静态变量避免重复调用
-    use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
-    static IS_SAVING: AtomicBool = AtomicBool::new(false);
-
-    // 如果正在保存，直接返回（使用顺序一致性保证原子性）
-    if IS_SAVING
-        .compare_exchange(false, true, SeqCst, SeqCst)
-        .is_err()
-    {
-        return Ok(());
-    }
-
-    // 先加载现有的窗口状态
-    let mut state = load_window_state().await?;
-
-    // 更新系统托盘状态
-    state.system_tray_enabled = enabled;
-
-    // 保存更新后的状态
-    let result = save_window_state(state).await;
-
-    // 释放保存锁（使用顺序一致性保证可见性）
-    IS_SAVING.store(false, SeqCst);
-
-    result
+    crate::settings::update(|settings| settings.system_tray_enabled = enabled)?;
+    println!(
+        "💾 系统托盘偏好已保存: {}",
+        if enabled { "启用" } else { "禁用" }
+    );
+    Ok(())
 }
 
 /// 获取系统托盘启用状态
 pub async fn get_system_tray_state() -> Result<bool, String> {
-    // 使用静态变量避免重复调用
-    use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
-    static IS_LOADING: AtomicBool = AtomicBool::new(false);
-
-    // 如果正在加载，返回缓存值或默认值（使用顺序一致性保证原子性）
-    if IS_LOADING
-        .compare_exchange(false, true, SeqCst, SeqCst)
-        .is_err()
-    {
-        return Ok(true); // 默认启用
-    }
-
-    let state = load_window_state().await;
-
-    // 释放加载锁（使用顺序一致性保证可见性）
-    IS_LOADING.store(false, SeqCst);
-
-    state.map(|s| s.system_tray_enabled)
+    Ok(crate::settings::get().system_tray_enabled)
 }
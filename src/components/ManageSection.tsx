@@ -4,6 +4,7 @@ import * as Dialog from '@radix-ui/react-dialog';
 import { Trash2 } from 'lucide-react';
 import { maskBackupFilename } from '../utils/username-masking';
 import { StandardTooltip } from './ui/tooltip';
+import type { SwitchReport } from '../types/tauri';
 
 const ManageSection = ({ backups, showStatus, onRefresh }) => {
   const [isClearDialogOpen, setIsClearDialogOpen] = useState(false);
@@ -44,11 +45,16 @@ const ManageSection = ({ backups, showStatus, onRefresh }) => {
     setSwitchingAccount(backupName);
     try {
       console.log('📞 调用后端 switch_to_antigravity_account 命令');
-      const result = await invoke('switch_to_antigravity_account', {
+      const report = await invoke<SwitchReport>('switch_to_antigravity_account', {
         accountName: backupName
       });
-      console.log('✅ 切换账户成功，结果:', result);
-      showStatus(`已切换到用户: ${backupName}`);
+      console.log('✅ 切换账户结束，结果:', report);
+      if (report.success) {
+        showStatus(`已切换到用户: ${backupName}`);
+      } else {
+        const failed = report.steps.find((step) => step.status === 'failed');
+        showStatus(`切换用户失败: ${failed?.message ?? report.summary}`, true);
+      }
     } catch (error) {
       console.error('❌ 切换用户失败:', error);
       showStatus(`切换用户失败: ${error}`, true);
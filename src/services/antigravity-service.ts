@@ -1,5 +1,6 @@
 import { invoke } from '@tauri-apps/api/core';
 import { open } from '@tauri-apps/plugin-dialog';
+import type { SwitchReport } from '../types/tauri';
 
 /**
  * Antigravity 服务 - 处理 Antigravity 相关操作
@@ -16,10 +17,13 @@ export class AntigravityService {
       onStatusUpdate('正在关闭 Antigravity 进程...');
 
       console.log('📞 调用后端 backup_and_restart_antigravity 命令');
-      const result = await invoke('backup_and_restart_antigravity') as string;
-      console.log('✅ 后端命令执行成功，结果:', result);
+      const report = await invoke<SwitchReport>('backup_and_restart_antigravity');
+      console.log('✅ 后端命令执行完成，结果:', report);
 
-      onStatusUpdate(result);
+      if (!report.success) {
+        throw new Error(report.summary);
+      }
+      onStatusUpdate(report.summary);
 
     } catch (error) {
       const errorMessage = error instanceof Error ? error.message : String(error);
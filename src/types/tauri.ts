@@ -26,11 +26,78 @@ export type BackupProfileResult = string;
 export type RestoreProfileResult = string;
 export type ListBackupsResult = string[];
 export type DeleteBackupResult = string;
-export type SwitchToAntigravityAccountResult = string;
+export type SwitchToAntigravityAccountResult = SwitchReport;
+
+// 账户切换步骤报告（与后端 account_switcher::SwitchReport 对应）
+export type SwitchStep =
+  | 'preflight'
+  | 'backup_current'
+  | 'stop'
+  | 'restore'
+  | 'logout'
+  | 'verify'
+  | 'launch';
+
+export type SwitchStepStatus = 'running' | 'succeeded' | 'skipped' | 'failed' | 'cancelled';
+
+export interface SwitchStepReport {
+  step: SwitchStep;
+  status: SwitchStepStatus;
+  message: string;
+  duration_ms: number;
+}
+
+export interface SwitchReport {
+  kind: 'switch' | 'switch_in_place' | 'backup_and_restart';
+  target: string | null;
+  account: { id: string; email: string; file_name: string } | null;
+  success: boolean;
+  cancelled: boolean;
+  steps: SwitchStepReport[];
+  summary: string;
+  duration_ms: number;
+}
 
 // 切换账户参数
 export interface SwitchToAntigravityAccountParams {
   account_name: string;
+  backup_current?: boolean;
+}
+
+// 登录状态变化事件（antigravity-auth://changed，与后端 auth_watcher::AuthEvent 对应）
+export type AuthEvent =
+  | { type: 'logged_in'; email: string; previous_email: string | null }
+  | { type: 'logged_out'; previous_email: string }
+  | { type: 'tokens_refreshed'; email: string };
+
+// 各类操作结果通知的开关（与后端 notifications::NotificationSettings 对应）
+export interface NotificationSettings {
+  switch: boolean;
+  backup: boolean;
+  restore: boolean;
+  logout: boolean;
+  scheduledJob: boolean;
+}
+
+// 应用设置（get_settings / update_settings，变化时发送 settings://changed 事件）
+export interface AgentSettings {
+  version: number;
+  antigravityPath: string | null;
+  systemTrayEnabled: boolean;
+  switchOptions: {
+    backupCurrent: boolean;
+    unknownAccount: 'prompt' | 'auto_save' | 'skip';
+  };
+  backupRefresh: 'auto' | 'prompt' | 'off';
+  quotaAction: 'off' | 'suggest' | 'switch';
+  cooldowns: {
+    quotaMinutes: number;
+    rateLimitMinutes: number;
+    authMinutes: number;
+  };
+  notifications: NotificationSettings;
+  launchAtLogin: boolean;
+  startHidden: boolean;
 }
 
 // 错误类型